@@ -0,0 +1,344 @@
+use crate::uci::{find_packet, mt, Field, Packet, ParamType, UciPacketParseError};
+use crate::uci::{APP_CONF_PARAMS, DATA_MESSAGE_SND_FIELDS, DEVICE_CONF_PARAMS, DEVICE_CONF_PARAMS_NXP, TEST_CONF_PARAMS};
+use crate::uci::{NXP_CIR_CAPTURE_CMD_FIELDS, NXP_CORE_DEVICE_INIT_CMD_FIELDS, NXP_DBG_LOG_CONFIG_CMD_FIELDS, NXP_TEST_LOOP_CMD_FIELDS};
+use crate::uci::{SESSION_ID_FIELDS, SESSION_INIT_CMD_FIELDS};
+use byteorder::{ByteOrder, LittleEndian};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+
+/* largest payload of a control packet; longer messages are segmented */
+const MAX_CTRL_PAYLOAD: usize = 255;
+
+/* Payload layout of an encodable message, in wire order */
+enum Part {
+    Static(&'static [Field]),
+    /* number of parameters followed by TLVs, as decoded by print_config() */
+    Config(&'static HashMap<u8, Field>, Option<&'static HashMap<(u8, u8), Field>>),
+    /* APPLICATION_DATA_LENGTH + APPLICATION_DATA */
+    AppData,
+}
+
+lazy_static! {
+    static ref LAYOUTS: HashMap<&'static str, Vec<Part>> = {
+        HashMap::from([
+            ("CORE_GET_DEVICE_INFO_CMD", vec![]),
+            ("CORE_GET_CAPS_INFO_CMD", vec![]),
+            ("CORE_SET_CONFIG_CMD", vec![Part::Config(&DEVICE_CONF_PARAMS, Some(&DEVICE_CONF_PARAMS_NXP))]),
+            ("SESSION_INIT_CMD", vec![Part::Static(&SESSION_INIT_CMD_FIELDS)]),
+            ("SESSION_SET_APP_CONFIG_CMD", vec![Part::Static(&SESSION_ID_FIELDS), Part::Config(&APP_CONF_PARAMS, None)]),
+            ("SESSION_DEINIT_CMD", vec![Part::Static(&SESSION_ID_FIELDS)]),
+            ("SESSION_GET_COUNT_CMD", vec![]),
+            ("SESSION_GET_STATE_CMD", vec![Part::Static(&SESSION_ID_FIELDS)]),
+            ("RANGE_START_CMD", vec![Part::Static(&SESSION_ID_FIELDS)]),
+            ("RANGE_STOP_CMD", vec![Part::Static(&SESSION_ID_FIELDS)]),
+            ("GET_RANGING_COUNT_CMD", vec![Part::Static(&SESSION_ID_FIELDS)]),
+            ("TEST_CONFIG_SET_CMD", vec![Part::Static(&SESSION_ID_FIELDS), Part::Config(&TEST_CONF_PARAMS, None)]),
+            ("TEST_RX_CMD", vec![]),
+            ("TEST_STOP_SESSION_CMD", vec![]),
+            ("TEST_SS_TWR_CMD", vec![]),
+            ("NXP_CORE_DEVICE_INIT_CMD", vec![Part::Static(&NXP_CORE_DEVICE_INIT_CMD_FIELDS)]),
+            ("NXP_SE_DO_BIND_CMD", vec![]),
+            ("NXP_SE_GET_BINDING_CNT_CMD", vec![]),
+            ("NXP_SE_GET_BINDING_STAT_CMD", vec![]),
+            ("NXP_TEST_LOOP_CMD", vec![Part::Static(&NXP_TEST_LOOP_CMD_FIELDS)]),
+            ("NXP_TEST_CONNECTIVITY_CMD", vec![]),
+            ("NXP_GET_INTF_STAT_CMD", vec![]),
+            ("NXP_DBG_LOG_CONFIG_CMD", vec![Part::Static(&NXP_DBG_LOG_CONFIG_CMD_FIELDS)]),
+            ("NXP_CIR_CAPTURE_CMD", vec![Part::Static(&NXP_CIR_CAPTURE_CMD_FIELDS)]),
+            ("DATA_MESSAGE_SND", vec![Part::Static(&DATA_MESSAGE_SND_FIELDS), Part::AppData]),
+        ])
+    };
+}
+
+fn error(msg: String) -> UciPacketParseError {
+    UciPacketParseError::new(&msg)
+}
+
+/* "0x1234" or "4660" */
+fn parse_number(s: &str) -> Option<u64> {
+    match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+/* "0a0b0c", "0x0a0b0c" or "0a:0b:0c" */
+fn parse_bytes(s: &str) -> Option<Vec<u8>> {
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    let hex: String = s.chars().filter(|c| !matches!(c, ':' | '-' | '_')).collect();
+    if hex.len() % 2 == 1 {
+        return None;
+    }
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok()).collect()
+}
+
+fn encode_uint(name: &str, val: &str, size: usize) -> Result<Vec<u8>, UciPacketParseError> {
+    match parse_number(val) {
+        Some(v) if size == 8 || v >> (size * 8) == 0 => Ok(v.to_le_bytes()[..size].to_vec()),
+        _ => Err(error(format!("{}: invalid value {} for a {}-byte field", name, val, size))),
+    }
+}
+
+fn encode_int(name: &str, val: &str, size: usize) -> Result<Vec<u8>, UciPacketParseError> {
+    let bits = size * 8;
+    match val.parse::<i64>() {
+        Ok(v) if v >= -(1i64 << (bits - 1)) && v < (1i64 << (bits - 1)) => Ok(v.to_le_bytes()[..size].to_vec()),
+        _ => Err(error(format!("{}: invalid value {} for a signed {}-byte field", name, val, size))),
+    }
+}
+
+/* "-12.5" for a signed Q9.7 field, rounded to the nearest step */
+fn encode_fixed(name: &str, val: &str, size: usize, frac: usize) -> Result<Vec<u8>, UciPacketParseError> {
+    let v = match val.parse::<f64>() {
+        Ok(v) => (v * (1u32 << frac) as f64).round() as i64,
+        Err(_) => return Err(error(format!("{}: invalid number {}", name, val))),
+    };
+    let bits = size * 8;
+    if v < -(1i64 << (bits - 1)) || v >= 1i64 << (bits - 1) {
+        return Err(error(format!("{}: {} out of range", name, val)));
+    }
+    Ok(v.to_le_bytes()[..size].to_vec())
+}
+
+fn encode_field(field: &Field, val: &str) -> Result<Vec<u8>, UciPacketParseError> {
+    let name = field.0;
+    let check_len = |bytes: Vec<u8>, n: usize| -> Result<Vec<u8>, UciPacketParseError> {
+        if n != 0 && bytes.len() != n {
+            return Err(error(format!("{}: expected {} bytes, got {}", name, n, bytes.len())));
+        }
+        Ok(bytes)
+    };
+
+    match field.1 {
+        ParamType::Table8(t) => match t.iter().find(|x| x.1.eq_ignore_ascii_case(val)) {
+            Some(x) => Ok(vec![x.0]),
+            None => encode_uint(name, val, 1),
+        },
+        ParamType::Map8(t) => match t.iter().find(|x| x.1.eq_ignore_ascii_case(val)) {
+            Some(x) => Ok(vec![*x.0]),
+            None => encode_uint(name, val, 1),
+        },
+        ParamType::HexArray(n) => match parse_bytes(val) {
+            Some(bytes) => check_len(bytes, n.max(0) as usize),
+            None => Err(error(format!("{}: invalid byte string {}", name, val))),
+        },
+        ParamType::Entries(_) => parse_bytes(val).ok_or_else(|| error(format!("{}: invalid byte string {}", name, val))),
+        ParamType::CharArray(n) => check_len(val.as_bytes().to_vec(), n.into()),
+        ParamType::RFU(n) => Ok(vec![0; n]),
+        ParamType::Int8 | ParamType::Int16 | ParamType::Int32 => encode_int(name, val, field.size()),
+        ParamType::Q16(n) | ParamType::Q8(n) | ParamType::Angle(n) => encode_fixed(name, val, field.size(), n),
+        _ => encode_uint(name, val, field.size()),
+    }
+}
+
+/* key/value arguments, consumed as the layout is walked */
+struct Args<'a>(Vec<(&'a str, &'a str)>);
+
+impl<'a> Args<'a> {
+    /* "session=..." selects SESSION_ID: exact name or name prefix up to an '_' */
+    fn take(&mut self, name: &str) -> Option<&'a str> {
+        let i = self.0.iter().position(|(k, _)| {
+            let k = k.to_ascii_uppercase();
+            name == k || name.starts_with(&(k + "_"))
+        })?;
+        Some(self.0.remove(i).1)
+    }
+}
+
+fn encode_config(args: &mut Args, table: &HashMap<u8, Field>,
+                 ext_table: Option<&HashMap<(u8, u8), Field>>) -> Result<Vec<u8>, UciPacketParseError> {
+    let mut tlvs = vec![0u8];
+    for (key, val) in args.0.drain(..) {
+        let by_name = |f: &Field| f.0.eq_ignore_ascii_case(key);
+        let (mut tlv, v) = if let Some((id, field)) = table.iter().find(|(_, f)| by_name(f)) {
+            (vec![*id], encode_field(field, val)?)
+        } else if let Some(((id0, id1), field)) = ext_table.and_then(|t| t.iter().find(|(_, f)| by_name(f))) {
+            (vec![*id0, *id1], encode_field(field, val)?)
+        } else if let Some(id) = parse_number(key).filter(|id| *id <= 0xff) {
+            /* unnamed parameter, given as raw id and bytes */
+            (vec![id as u8], parse_bytes(val).ok_or_else(|| error(format!("{}: invalid byte string {}", key, val)))?)
+        } else {
+            return Err(error(format!("unknown parameter {}", key)));
+        };
+        if v.len() > 0xff || tlvs[0] == 0xff {
+            return Err(error(format!("{}: does not fit in a TLV list", key)));
+        }
+        tlv.push(v.len() as u8);
+        tlv.extend(v);
+        tlvs.extend(tlv);
+        tlvs[0] += 1;
+    }
+    Ok(tlvs)
+}
+
+/*
+ * Builds the packets of a message from a line such as
+ * "SESSION_SET_APP_CONFIG session=0x1234 CHANNEL_NUMBER=9 SLOT_DURATION=2400".
+ * The message name is the one printed by the decoder; "_CMD" may be omitted.
+ */
+pub fn encode(line: &str) -> Result<Vec<Packet>, UciPacketParseError> {
+    let mut tokens = line.split_whitespace();
+    let name = match tokens.next() {
+        Some(name) => name.to_ascii_uppercase(),
+        None => return Err(error("empty command".to_string())),
+    };
+    let (name, layout) = match LAYOUTS.get_key_value(name.as_str()).or_else(|| LAYOUTS.get_key_value(format!("{}_CMD", name).as_str())) {
+        Some((name, layout)) => (*name, layout),
+        None => return Err(error(format!("no encoder for {}", name))),
+    };
+    let id = find_packet(name).ok_or_else(|| error(format!("unknown message {}", name)))?;
+
+    let mut args = Args(Vec::new());
+    for token in tokens {
+        match token.split_once('=') {
+            Some(kv) => args.0.push(kv),
+            None => return Err(error(format!("expected key=value, got {}", token))),
+        }
+    }
+
+    let mut payload = Vec::new();
+    for part in layout {
+        match part {
+            Part::Static(fields) => {
+                for field in fields.iter() {
+                    if let ParamType::RFU(n) = field.1 {
+                        payload.extend(vec![0; n]);
+                        continue;
+                    }
+                    let val = args.take(field.0).ok_or_else(|| error(format!("missing {}", field.0)))?;
+                    payload.extend(encode_field(field, val)?);
+                }
+            }
+            Part::Config(table, ext_table) => payload.extend(encode_config(&mut args, table, *ext_table)?),
+            Part::AppData => {
+                let data = match args.take("APPLICATION_DATA") {
+                    Some(val) => parse_bytes(val).ok_or_else(|| error(format!("APPLICATION_DATA: invalid byte string {}", val)))?,
+                    None => Vec::new(),
+                };
+                let mut len = [0u8; 2];
+                LittleEndian::write_u16(&mut len, data.len() as u16);
+                payload.extend(len);
+                payload.extend(data);
+            }
+        }
+    }
+    if let Some((k, _)) = args.0.first() {
+        return Err(error(format!("unknown parameter {}", k)));
+    }
+
+    if id.2 == mt::DATA {
+        if payload.len() > u16::MAX.into() {
+            return Err(error(format!("data payload too long: {}", payload.len())));
+        }
+        return Ok(vec![Packet::build(&id, false, &payload)]);
+    }
+    if payload.is_empty() {
+        return Ok(vec![Packet::build(&id, false, &payload)]);
+    }
+    let n = payload.len().div_ceil(MAX_CTRL_PAYLOAD);
+    Ok(payload.chunks(MAX_CTRL_PAYLOAD).enumerate()
+       .map(|(i, chunk)| Packet::build(&id, i + 1 < n, chunk))
+       .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{decode, decode_packet, Reassembler, SessionCmd, UciMessage, Value};
+
+    fn encode_one(line: &str) -> Packet {
+        let pkts = encode(line).unwrap_or_else(|e| panic!("{}: {}", line, e));
+        assert_eq!(pkts.len(), 1);
+        pkts.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn session_set_app_config() {
+        let pkt = encode_one("SESSION_SET_APP_CONFIG session=0x1234 CHANNEL_NUMBER=9 SLOT_DURATION=2400");
+        assert_eq!(pkt.bytes(), [0x21, 0x03, 0x00, 0x0c, 0x34, 0x12, 0x00, 0x00, 0x02, 0x04, 0x01, 0x09, 0x08, 0x02, 0x60, 0x09]);
+
+        let msg = decode(pkt.bytes()).unwrap();
+        let UciMessage::SessionSetAppConfigCmd(cmd) = msg else { panic!("{:?}", msg) };
+        assert_eq!(cmd.session_id, 0x1234);
+        assert_eq!(cmd.config.get("CHANNEL_NUMBER(0x04)"), Some(&Value::Hex(9)));
+        assert_eq!(cmd.config.get("SLOT_DURATION(0x08)"), Some(&Value::Dec(2400)));
+    }
+
+    #[test]
+    fn table_values_by_name() {
+        let pkt = encode_one("SESSION_SET_APP_CONFIG_CMD SESSION_ID=1 DEVICE_ROLE=initiator STATIC_STS_IV=010203040506");
+        let msg = decode(pkt.bytes()).unwrap();
+        let UciMessage::SessionSetAppConfigCmd(cmd) = msg else { panic!("{:?}", msg) };
+        assert_eq!(cmd.config.get("DEVICE_ROLE"), Some(&Value::Enum(1, Some("Initiator"))));
+        assert_eq!(cmd.config.get("STATIC_STS_IV"), Some(&Value::Bytes(vec![1, 2, 3, 4, 5, 6])));
+    }
+
+    #[test]
+    fn core_set_config_with_nxp_params() {
+        let pkt = encode_one("CORE_SET_CONFIG LOW_POWER_MODE=1 DPD_ENTRY_TIMEOUT=500");
+        assert_eq!(pkt.bytes(), [0x20, 0x04, 0x00, 0x09, 0x02, 0x01, 0x01, 0x01, 0xe4, 0x04, 0x02, 0xf4, 0x01]);
+        let msg = decode(pkt.bytes()).unwrap();
+        let UciMessage::CoreSetConfigCmd(config) = msg else { panic!("{:?}", msg) };
+        assert_eq!(config.get("DPD_ENTRY_TIMEOUT(0xe4:0x04)"), Some(&Value::Dec(500)));
+    }
+
+    #[test]
+    fn session_init_and_range_start() {
+        let pkt = encode_one("SESSION_INIT session=0x11223344 session_type=0");
+        assert_eq!(pkt.bytes(), [0x21, 0x00, 0x00, 0x05, 0x44, 0x33, 0x22, 0x11, 0x00]);
+        let pkt = encode_one("range_start session_id=5");
+        assert_eq!(decode(pkt.bytes()).unwrap(), UciMessage::RangeStartCmd(SessionCmd { session_id: 5 }));
+    }
+
+    #[test]
+    fn data_message() {
+        let pkt = encode_one("DATA_MESSAGE_SND SESSION_HANDLE=0x12345678 DESTINATION_ADDRESS=aabbccddeeff0011 SEQUENCE_NUMBER=1 APPLICATION_DATA=010203");
+        assert_eq!(pkt.len(), 19);
+        let msg = decode(pkt.bytes()).unwrap();
+        let UciMessage::DataMessageSnd(msg) = msg else { panic!("{:?}", msg) };
+        assert_eq!(msg.name, "DATA_MESSAGE_SND");
+        assert_eq!(msg.get("APPLICATION_DATA"), Some(&Value::Bytes(vec![1, 2, 3])));
+    }
+
+    #[test]
+    fn segmented_round_trip() {
+        let line = format!("CORE_SET_CONFIG DDFS_TONE_CONFIG={} CLK_CONFIG_CTRL=0102 {}",
+                           "ab".repeat(72), (0..80).map(|_| "LOW_POWER_MODE=0").collect::<Vec<_>>().join(" "));
+        let pkts = encode(&line).unwrap();
+        assert_eq!(pkts.len(), 2);
+        assert!(pkts[0].pbf() && !pkts[1].pbf());
+
+        let mut reassembler = Reassembler::new();
+        let mut out: Vec<Packet> = pkts.into_iter().flat_map(|p| reassembler.push(p)).map(|r| r.unwrap()).collect();
+        assert_eq!(out.len(), 1);
+        let msg = decode_packet(&out.remove(0)).unwrap();
+        let UciMessage::CoreSetConfigCmd(config) = msg else { panic!("{:?}", msg) };
+        assert_eq!(config.params.len(), 82);
+        assert_eq!(config.get("CLK_CONFIG_CTRL(0xe4:0x30)"), Some(&Value::Bytes(vec![1, 2])));
+    }
+
+    #[test]
+    fn fixed_point() {
+        assert_eq!(encode_fixed("AoA", "-13.5", 2, 7).unwrap(), vec![0x40, 0xf9]);
+        assert_eq!(encode_fixed("RSSI", "-39.5", 1, 1).unwrap(), vec![0xb1]);
+        assert_eq!(encode_fixed("RSSI", "-64", 1, 1).unwrap(), vec![0x80]);
+        assert!(encode_fixed("RSSI", "64", 1, 1).is_err());
+        assert!(encode_fixed("AoA", "300", 2, 7).is_err());
+        assert_eq!(encode_int("TX", "-2", 2).unwrap(), vec![0xfe, 0xff]);
+        assert!(encode_int("TX", "128", 1).is_err());
+    }
+
+    #[test]
+    fn errors() {
+        let error = |line: &str| encode(line).err().map(|e| e.to_string());
+        assert_eq!(error("SESSION_INIT session=1").as_deref(), Some("missing SESSION_TYPE"));
+        assert_eq!(error("SESSION_INIT session=1 session_type=0x100").as_deref(), Some("SESSION_TYPE: invalid value 0x100 for a 1-byte field"));
+        assert_eq!(error("SESSION_INIT session=1 type=0").as_deref(), Some("missing SESSION_TYPE"));
+        assert_eq!(error("SESSION_INIT session=1 session_type=0 channel=9").as_deref(), Some("unknown parameter channel"));
+        assert_eq!(error("SESSION_SET_APP_CONFIG session=1 NO_SUCH_PARAM=1").as_deref(), Some("unknown parameter NO_SUCH_PARAM"));
+        assert_eq!(error("STATIC_STS_IV=01").as_deref(), Some("no encoder for STATIC_STS_IV=01"));
+        assert_eq!(error("SESSION_SET_APP_CONFIG session=1 STATIC_STS_IV=01").as_deref(), Some("STATIC_STS_IV: expected 6 bytes, got 1"));
+        assert_eq!(error("SESSION_SET_APP_CONFIG session=1 CHANNEL_NUMBER=nine").as_deref(), Some("CHANNEL_NUMBER: invalid value nine for a 1-byte field"));
+        assert_eq!(error("").as_deref(), Some("empty command"));
+    }
+}
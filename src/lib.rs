@@ -0,0 +1,19 @@
+mod encoder;
+pub mod logcat;
+mod message;
+pub mod pcap;
+mod printer;
+mod uci;
+
+pub use encoder::encode;
+pub use message::{Message, Param, Value};
+pub use message::{
+    Aoa, Code, Config, ConfigRsp, ConfigStatus, Controlee, ControleeStatus, CoreDeviceStatusNtf, CoreGetDeviceInfoRsp,
+    DeviceState, DlTdoaMeasurement, Empty, GetAppConfigCmd, GetConfigCmd, GetRangingCountRsp, MacAddress, Measurement,
+    OwrAoaMeasurement, RangeCccDataNtf, RangeDataNtf, SessionCmd, SessionGetCountRsp, SessionGetStateRsp, SessionInitCmd,
+    SessionInitRsp, SessionState, SessionStatusNtf, SessionUpdateControllerMulticastListCmd,
+    SessionUpdateControllerMulticastListNtf, SetAppConfigCmd, SetConfigRsp, StatusOnly, TdoaMeasurement, TwrMeasurement,
+    Version,
+};
+pub use printer::{BasicPrinter, JsonPrinter, Printer};
+pub use uci::{decode, decode_packet, decode_packet_with, decode_with, Direction, FrameInfo, OutputFormat, Packet, Parser, Reassembler, UciMessage, UciPacketParseError, UciVersion};
@@ -0,0 +1,128 @@
+use crate::uci::{Direction, FrameInfo};
+
+/* A UCI frame found in a logcat line */
+pub struct LogcatFrame {
+    pub info: FrameInfo,
+    pub hex: String,
+}
+
+/* leading "MM-DD hh:mm:ss.mmm" (threadtime) or "YYYY-MM-DD hh:mm:ss.mmm" (-v year) */
+fn timestamp(line: &str) -> Option<String> {
+    let mut tokens = line.split_whitespace();
+    let date = tokens.next()?;
+    let time = tokens.next()?;
+    let is_date = !date.is_empty() && date.chars().all(|c| c.is_ascii_digit() || c == '-') && date.contains('-');
+    let is_time = time.chars().all(|c| c.is_ascii_digit() || c == ':' || c == '.') && time.contains(':');
+    if is_date && is_time {
+        Some(format!("{} {}", date, time))
+    } else {
+        None
+    }
+}
+
+/* hex digits following `s`, allowing bytes separated by blanks */
+fn hex_payload(s: &str) -> Option<String> {
+    let mut hex = String::new();
+    for token in s.split_whitespace() {
+        if !token.chars().all(|c| c.is_ascii_hexdigit()) || token.len() % 2 == 1 {
+            break;
+        }
+        hex.push_str(token);
+    }
+    if hex.len() >= 8 {
+        Some(hex)
+    } else {
+        None
+    }
+}
+
+fn direction(s: &str) -> Option<Direction> {
+    for word in s.split(|c: char| !c.is_ascii_alphanumeric()) {
+        match word.to_ascii_lowercase().as_str() {
+            "send" | "tx" => return Some(Direction::HostToUwbs),
+            "recv" | "rx" => return Some(Direction::UwbsToHost),
+            _ => (),
+        }
+    }
+    None
+}
+
+/*
+ * Recognized layouts:
+ *   NxpUciHal: NxpUciHal:Send len=4 data=20000001
+ *   NxpUciX: len =   4 > 20000001
+ *   NxpUciR: len =   5 < 4000000100
+ *   uwb_uci: ... > 20000001
+ */
+pub fn parse_line(line: &str) -> Option<LogcatFrame> {
+    let (prefix, payload, dir) = if let Some(i) = line.find("data=").or_else(|| line.find("data:")) {
+        (&line[..i], &line[i + 5..], None)
+    } else if let Some(i) = line.rfind(" > ") {
+        (&line[..i], &line[i + 3..], Some(Direction::HostToUwbs))
+    } else if let Some(i) = line.rfind(" < ") {
+        (&line[..i], &line[i + 3..], Some(Direction::UwbsToHost))
+    } else {
+        return None;
+    };
+
+    /* skip the logcat header when looking for direction keywords */
+    let message = match prefix.find(": ") {
+        Some(i) => &prefix[i + 2..],
+        None => prefix,
+    };
+
+    Some(LogcatFrame {
+        info: FrameInfo {
+            timestamp: timestamp(line),
+            direction: dir.or_else(|| direction(message)),
+        },
+        hex: hex_payload(payload)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> (Option<String>, Option<Direction>, String) {
+        let frame = parse_line(line).unwrap();
+        (frame.info.timestamp, frame.info.direction, frame.hex)
+    }
+
+    #[test]
+    fn nxp_hal() {
+        assert_eq!(parse("10-16 22:48:20.123  1234  5678 D NxpUciHal: NxpUciHal:Send len=4 data=20000001"),
+                   (Some("10-16 22:48:20.123".to_string()), Some(Direction::HostToUwbs), "20000001".to_string()));
+        assert_eq!(parse("10-16 22:48:20.130  1234  5679 D NxpUciHal: NxpUciHal:Recv len=5 data=4000000100"),
+                   (Some("10-16 22:48:20.130".to_string()), Some(Direction::UwbsToHost), "4000000100".to_string()));
+        /* "data:" with bytes separated by blanks */
+        assert_eq!(parse("10-16 22:48:20.131 D NxpUciHal: Recv data: 60 01 00 01 01").2, "6001000101");
+    }
+
+    #[test]
+    fn nxp_uci_tags() {
+        assert_eq!(parse("2026-10-16 22:48:20.123  1234  5678 D NxpUciX : len =   4 > 20000001"),
+                   (Some("2026-10-16 22:48:20.123".to_string()), Some(Direction::HostToUwbs), "20000001".to_string()));
+        assert_eq!(parse("10-16 22:48:20.125  1234  5678 D NxpUciR : len =   5 < 4000000100"),
+                   (Some("10-16 22:48:20.125".to_string()), Some(Direction::UwbsToHost), "4000000100".to_string()));
+    }
+
+    #[test]
+    fn uwb_uci() {
+        assert_eq!(parse("10-16 22:48:21.000  1000  1001 I uwb_uci: uci_send > 2100000501000000a0"),
+                   (Some("10-16 22:48:21.000".to_string()), Some(Direction::HostToUwbs), "2100000501000000a0".to_string()));
+        /* no logcat header: no timestamp */
+        assert_eq!(parse("uwb_uci: < 4100000100"), (None, Some(Direction::UwbsToHost), "4100000100".to_string()));
+    }
+
+    #[test]
+    fn not_uci() {
+        assert!(parse_line("").is_none());
+        assert!(parse_line("10-16 22:48:20.123  1234  5678 D WifiService: enabled").is_none());
+        /* too short to hold a header */
+        assert!(parse_line("10-16 22:48:20.123 D NxpUciHal: Send len=1 data=20").is_none());
+        /* not hex */
+        assert!(parse_line("10-16 22:48:20.123 D foo: a > b").is_none());
+        assert!(parse_line("10-16 22:48:20.123 D NxpUciHal: data=zz000001").is_none());
+    }
+}
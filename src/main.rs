@@ -1,33 +1,87 @@
 use std::env;
-mod uci;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use parse_uci::{encode, logcat, pcap, FrameInfo, OutputFormat, Packet, Parser, UciVersion};
+
+/* "-" reads standard input */
+fn open_input(path: &str) -> std::io::Result<Box<dyn BufRead>> {
+    if path == "-" {
+        Ok(Box::new(BufReader::new(std::io::stdin())))
+    } else {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+/* frames of a logcat capture or of a list of hex strings, for pcapng export */
+fn read_frames(kind: &str, input: Box<dyn BufRead>) -> std::io::Result<Vec<(Packet, FrameInfo)>> {
+    let mut frames = Vec::new();
+    for line in input.lines() {
+        let line = line?;
+        let (hex, info) = match kind {
+            "logcat" => match logcat::parse_line(&line) {
+                Some(frame) => (frame.hex, frame.info),
+                None => continue,
+            },
+            _ => (line.trim().to_string(), FrameInfo::default()),
+        };
+        if hex.is_empty() {
+            continue;
+        }
+        match Packet::from_hex(&hex) {
+            Ok(pkt) => frames.push((pkt, info)),
+            Err(e) => println!("skipped {}: {}", hex, e),
+        }
+    }
+    Ok(frames)
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut format = OutputFormat::Text;
+    let mut version = None;
+    let args: Vec<String> = env::args().filter(|arg| {
+        match arg.as_str() {
+            "--json" => { format = OutputFormat::Json; false }
+            /* otherwise detected from CORE_GET_DEVICE_INFO_RSP */
+            "--uci=1" | "--uci=1.0" | "--uci=1.1" => { version = Some(UciVersion::V1); false }
+            "--uci=2" | "--uci=2.0" => { version = Some(UciVersion::V2); false }
+            _ => true,
+        }
+    }).collect();
 
     if args.len() < 2 {
         println!("no argument!");
         return
     }
 
+    let mut parser = Parser::with_format(format);
+    if let Some(version) = version {
+        parser.set_version(version);
+    }
+
     if args[1] == "sh" {
         loop {
             use std::io::{stdin, stdout, Write};
-            print!("> ");
-            let _ = stdout().flush();
+            /* keep stdout machine readable in JSON mode */
+            if format == OutputFormat::Text {
+                print!("> ");
+                let _ = stdout().flush();
+            }
             let mut input = String::new();
             match stdin().read_line(&mut input) {
                 Ok(size) => {
                     if size == 0 {
                         break;
                     }
-                    if Some('\n') == input.chars().last() {
+                    if input.ends_with('\n') {
                         input.pop();
                     }
-                    if Some('\r') == input.chars().last() {
+                    if input.ends_with('\r') {
                         input.pop();
                     }
-                    println!("{}", input);
-                    uci::parse(input);
+                    if format == OutputFormat::Text {
+                        println!("{}", input);
+                    }
+                    parser.parse(input);
                 }
                 Err(err)  => {
                     println!("{}", err);
@@ -36,7 +90,71 @@ fn main() {
             }
         }
 
+    } else if args[1] == "logcat" {
+        let input = match open_input(args.get(2).map(|s| s.as_str()).unwrap_or("-")) {
+            Ok(input) => input,
+            Err(err) => {
+                println!("{}", err);
+                return
+            }
+        };
+        for line in input.lines() {
+            match line {
+                Ok(line) => {
+                    if let Some(frame) = logcat::parse_line(&line) {
+                        parser.parse_frame(frame.hex, &frame.info);
+                    }
+                }
+                Err(err) => {
+                    println!("{}", err);
+                    break;
+                }
+            }
+        }
+    } else if args[1] == "pcap" {
+        let frames = match args.get(2).map(std::fs::read) {
+            Some(Ok(data)) => pcap::read(&data),
+            Some(Err(err)) => Err(err),
+            None => {
+                println!("usage: {} pcap <capture>", args[0]);
+                return
+            }
+        };
+        match frames {
+            Ok(frames) => {
+                for frame in frames {
+                    parser.parse_bytes(&frame.bytes, &frame.info);
+                }
+            }
+            Err(err) => println!("{}", err),
+        }
+    } else if args[1] == "encode" {
+        match encode(&args[2..].join(" ")) {
+            Ok(pkts) => {
+                for pkt in pkts {
+                    println!("{}", pkt.bytes().iter().map(|b| format!("{:02x}", b)).collect::<String>());
+                }
+            }
+            Err(e) => println!("{}", e),
+        }
+        return
+    } else if args[1] == "export" {
+        if args.len() < 5 || (args[2] != "logcat" && args[2] != "hex") {
+            println!("usage: {} export <logcat|hex> <input|-> <output.pcapng>", args[0]);
+            return
+        }
+        let ret = open_input(&args[3])
+            .and_then(|input| read_frames(&args[2], input))
+            .and_then(|frames| {
+                println!("{} frames", frames.len());
+                pcap::export(File::create(&args[4])?, &frames)
+            });
+        if let Err(err) = ret {
+            println!("{}", err);
+        }
+        return
     } else {
-        uci::parse(args[1].to_string());
+        parser.parse(args[1].to_string());
     }
+    parser.finish();
 }
@@ -0,0 +1,864 @@
+use crate::uci::UciPacketParseError;
+use std::fmt;
+
+/*
+ * Decoded value of a single field. Values are typed by their encoding (integer,
+ * enumeration, fixed point, ...), not by the field they belong to.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Hex(u32),
+    Dec(u32),
+    Int(i32),
+    Bytes(Vec<u8>),
+    Text(String),
+    /* raw id and its name, if known */
+    Enum(u8, Option<&'static str>),
+    /* fixed-point number, its unit if any and the raw integer */
+    Fixed(f64, Option<&'static str>, u32),
+    /* bitmask and the names of its set bits */
+    Flags(u32, Vec<String>),
+    /* repeated structure, e.g. range measurements */
+    List(Vec<Vec<Param>>),
+    /* field could not be decoded, with the reason */
+    Invalid(String),
+}
+
+impl Value {
+    /* integer behind Hex, Dec, Enum and Flags values, and raw Int bits */
+    pub fn as_u32(&self) -> Option<u32> {
+        match self {
+            Value::Hex(v) | Value::Dec(v) | Value::Flags(v, _) => Some(*v),
+            Value::Int(v) => Some(*v as u32),
+            Value::Enum(v, _) => Some((*v).into()),
+            _ => None,
+        }
+    }
+
+    /* numeric value, with fixed-point numbers scaled */
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Fixed(v, _, _) => Some(*v),
+            Value::Int(v) => Some((*v).into()),
+            _ => self.as_u32().map(f64::from),
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Vec<Param>]> {
+        match self {
+            Value::List(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+pub(crate) fn hexarr(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::from("{"), |arr, b| arr + format!(" {:#04x}", b).as_str()) + " }"
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Hex(v) => write!(f, "{:#x}", v),
+            Value::Dec(v) => write!(f, "{}", v),
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Bytes(v) => write!(f, "{}", hexarr(v)),
+            Value::Text(s) => write!(f, "{}", s),
+            Value::Enum(id, Some(name)) => write!(f, "{:#04x} ({})", id, name),
+            Value::Enum(id, None) => write!(f, "{:#04x}(Unknown)", id),
+            Value::Fixed(v, Some(unit), raw) => write!(f, "{} {} ({:#x})", v, unit, raw),
+            Value::Fixed(v, None, raw) => write!(f, "{} ({:#x})", v, raw),
+            Value::Flags(v, names) if names.is_empty() => write!(f, "{:#x} (none)", v),
+            Value::Flags(v, names) => write!(f, "{:#x} ({})", v, names.join(" | ")),
+            Value::List(items) => write!(f, "{} items", items.len()),
+            Value::Invalid(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/* A named field with its location in the payload */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    /* payload offset; None for values that are not read from a fixed location */
+    pub offset: Option<usize>,
+    pub raw: Vec<u8>,
+    pub value: Value,
+}
+
+/*
+ * A decoded packet: its fields in payload order, as printed. Vendor, test and
+ * data packets are decoded to it, the others to the typed payloads below,
+ * which are read back from it. For data packets `gid` holds the DPF and `oid` is 0.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub name: &'static str,
+    pub mt: u8,
+    pub gid: u8,
+    pub oid: u8,
+    pub params: Vec<Param>,
+    pub comments: Vec<String>,
+}
+
+impl Message {
+    /* first field named `name`; configuration parameters also match without their "(0x..)" id */
+    pub fn param(&self, name: &str) -> Option<&Param> {
+        find_param(&self.params, name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.param(name).map(|p| &p.value)
+    }
+}
+
+fn find_param<'a>(params: &'a [Param], name: &str) -> Option<&'a Param> {
+    params.iter().find(|p| {
+        p.name == name || p.name.strip_prefix(name).is_some_and(|id| id.starts_with("(0x") && id.ends_with(')'))
+    })
+}
+
+/* raw id of an enumerated field, e.g. a status or reason code, and its name if known */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Code {
+    pub value: u8,
+    pub name: Option<&'static str>,
+}
+
+/* UCI version: major.minor.maintenance */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+    pub maintenance: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeviceState {
+    Ready,
+    Active,
+    Error,
+    Unknown(u8),
+}
+
+impl From<u8> for DeviceState {
+    fn from(v: u8) -> Self {
+        match v {
+            0x01 => DeviceState::Ready,
+            0x02 => DeviceState::Active,
+            0xff => DeviceState::Error,
+            _ => DeviceState::Unknown(v),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionState {
+    Init,
+    Deinit,
+    Active,
+    Idle,
+    Unknown(u8),
+}
+
+impl From<u8> for SessionState {
+    fn from(v: u8) -> Self {
+        match v {
+            0x00 => SessionState::Init,
+            0x01 => SessionState::Deinit,
+            0x02 => SessionState::Active,
+            0x03 => SessionState::Idle,
+            _ => SessionState::Unknown(v),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MacAddress {
+    Short(u16),
+    Extended(u64),
+}
+
+/* angle of arrival in degrees and its figure of merit */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aoa {
+    pub angle: f64,
+    pub fom: u8,
+}
+
+/* configuration parameters in payload order, named as printed, e.g. "CHANNEL_NUMBER(0x04)" */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub params: Vec<Param>,
+}
+
+impl Config {
+    /* parameter named `name`, with or without its "(0x..)" id */
+    pub fn param(&self, name: &str) -> Option<&Param> {
+        find_param(&self.params, name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.param(name).map(|p| &p.value)
+    }
+}
+
+/*
+ * Typed payloads of the CORE, SESSION and RANGING messages. Session IDs are
+ * the session handles in UCI 2.0.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct Empty;
+
+/* responses and notifications made of a status */
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusOnly {
+    pub status: Code,
+}
+
+/* commands naming a session only */
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionCmd {
+    pub session_id: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoreDeviceStatusNtf {
+    pub state: DeviceState,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoreGetDeviceInfoRsp {
+    pub status: Code,
+    pub uci_version: Version,
+    pub mac_version: Version,
+    pub phy_version: Version,
+    pub uci_test_version: Version,
+    pub vendor_info: Vec<u8>,
+    /* the vendor info as NXP extended TLVs, if it is */
+    pub vendor_config: Config,
+}
+
+/* GET_CONFIG and GET_CAPS_INFO responses; a failed request has no parameters */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigRsp {
+    pub status: Code,
+    pub config: Config,
+}
+
+/* status of a parameter rejected by a SET_CONFIG command, by its raw id */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigStatus {
+    pub id: Vec<u8>,
+    pub status: Code,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetConfigRsp {
+    pub status: Code,
+    pub params: Vec<ConfigStatus>,
+}
+
+/* raw ids of the requested parameters */
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetConfigCmd {
+    pub ids: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetAppConfigCmd {
+    pub session_id: u32,
+    pub config: Config,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetAppConfigCmd {
+    pub session_id: u32,
+    pub ids: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionInitCmd {
+    pub session_id: u32,
+    pub session_type: Code,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionInitRsp {
+    pub status: Code,
+    /* UCI 2.0 */
+    pub session_handle: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionStatusNtf {
+    pub session_id: u32,
+    pub state: SessionState,
+    pub reason: Code,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionGetCountRsp {
+    pub status: Code,
+    pub count: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionGetStateRsp {
+    pub status: Code,
+    pub state: SessionState,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Controlee {
+    pub short_address: u16,
+    pub sub_session_id: u32,
+    pub sub_session_key: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionUpdateControllerMulticastListCmd {
+    pub session_id: u32,
+    pub action: Code,
+    pub controlees: Vec<Controlee>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControleeStatus {
+    pub mac_address: u16,
+    /* UCI 1.x */
+    pub sub_session_id: Option<u32>,
+    pub status: Code,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionUpdateControllerMulticastListNtf {
+    pub session_id: u32,
+    /* UCI 1.x */
+    pub remaining_multicast_list_size: Option<u8>,
+    pub controlees: Vec<ControleeStatus>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetRangingCountRsp {
+    pub status: Code,
+    /* missing if the request failed */
+    pub count: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeDataNtf {
+    pub sequence_number: u32,
+    pub session_id: u32,
+    /* UCI 2.0 */
+    pub rcr_indication: Option<u8>,
+    pub ranging_interval: u32,
+    pub ranging_type: Code,
+    pub mac_addressing_mode: Code,
+    pub measurements: Vec<Measurement>,
+    /* what follows the measurements, e.g. NXP vendor data */
+    pub vendor: Vec<Param>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Measurement {
+    Twr(TwrMeasurement),
+    Tdoa(TdoaMeasurement),
+    DlTdoa(DlTdoaMeasurement),
+    OwrAoa(OwrAoaMeasurement),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TwrMeasurement {
+    pub mac_address: MacAddress,
+    pub status: Code,
+    pub nlos: Code,
+    pub distance: u16,
+    pub aoa_azimuth: Aoa,
+    pub aoa_elevation: Aoa,
+    pub aoa_destination_azimuth: Aoa,
+    pub aoa_destination_elevation: Aoa,
+    pub slot_index: u8,
+    /* UCI 2.0 */
+    pub rssi: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TdoaMeasurement {
+    pub mac_address: MacAddress,
+    pub status: Code,
+    pub message_control: u8,
+    pub frame_type: Code,
+    pub nlos: Code,
+    pub aoa_azimuth: Aoa,
+    pub aoa_elevation: Aoa,
+    pub frame_number: u32,
+    pub rx_timestamp: u64,
+    pub device_id: Option<Vec<u8>>,
+    pub tx_timestamp: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DlTdoaMeasurement {
+    pub mac_address: MacAddress,
+    pub status: Code,
+    pub message_type: Code,
+    pub message_control: u16,
+    pub block_index: u16,
+    pub round_index: u8,
+    pub nlos: Code,
+    pub aoa_azimuth: Aoa,
+    pub aoa_elevation: Aoa,
+    pub rssi: f64,
+    pub tx_timestamp: u64,
+    pub rx_timestamp: u64,
+    pub anchor_cfo: f64,
+    pub cfo: f64,
+    pub initiator_reply_time: u32,
+    pub responder_reply_time: u32,
+    pub initiator_responder_tof: u16,
+    pub anchor_location: Option<Vec<u8>>,
+    pub active_ranging_rounds: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwrAoaMeasurement {
+    pub mac_address: MacAddress,
+    pub status: Code,
+    pub nlos: Code,
+    pub frame_sequence_number: u8,
+    pub block_index: u16,
+    pub aoa_azimuth: Aoa,
+    pub aoa_elevation: Aoa,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeCccDataNtf {
+    pub session_id: u32,
+    pub ranging_status: Code,
+    pub sts_index: u32,
+    pub rr_index: u16,
+    pub distance: u16,
+    pub uncertainty_anchor_fom: u8,
+    pub uncertainty_initiator_fom: u8,
+    pub ccm_tag: Vec<u8>,
+    pub aoa_azimuth: Aoa,
+    pub aoa_elevation: Aoa,
+}
+
+/* payload of a UciMessage variant, read back from the fields its decoder printed */
+pub(crate) trait FromMessage: Sized {
+    fn from_message(msg: Message) -> Result<Self, UciPacketParseError>;
+}
+
+impl FromMessage for Message {
+    fn from_message(msg: Message) -> Result<Self, UciPacketParseError> {
+        Ok(msg)
+    }
+}
+
+trait FromFields: Sized {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError>;
+}
+
+impl<T: FromFields> FromMessage for T {
+    fn from_message(msg: Message) -> Result<Self, UciPacketParseError> {
+        T::from_fields(&Fields(&msg.params)).map_err(|e| UciPacketParseError::new(&format!("{}: {}", msg.name, e)))
+    }
+}
+
+/* the fields of a message or of a list item, by their printed names */
+struct Fields<'a>(&'a [Param]);
+
+impl<'a> Fields<'a> {
+    fn param(&self, name: &str) -> Option<&'a Param> {
+        self.0.iter().find(|p| p.name == name)
+    }
+
+    fn get(&self, name: &str) -> Result<&'a Param, UciPacketParseError> {
+        self.param(name).ok_or_else(|| UciPacketParseError::new(&format!("missing {}", name)))
+    }
+
+    /* the first of `names` present, e.g. SESSION_ID in UCI 1.x and SESSION_HANDLE in 2.0 */
+    fn any(&self, names: &[&str]) -> Result<&'a Param, UciPacketParseError> {
+        names.iter().find_map(|name| self.param(name)).ok_or_else(|| UciPacketParseError::new(&format!("missing {}", names.join("/"))))
+    }
+
+    fn opt<T>(&self, name: &str, read: impl Fn(&Param) -> Result<T, UciPacketParseError>) -> Result<Option<T>, UciPacketParseError> {
+        self.param(name).map(read).transpose()
+    }
+
+    fn num<T: TryFrom<u32>>(&self, name: &str) -> Result<T, UciPacketParseError> {
+        num(self.get(name)?)
+    }
+
+    fn fixed(&self, name: &str) -> Result<f64, UciPacketParseError> {
+        let p = self.get(name)?;
+        p.value.as_f64().ok_or_else(|| invalid(p))
+    }
+
+    fn code(&self, name: &str) -> Result<Code, UciPacketParseError> {
+        code(self.get(name)?)
+    }
+
+    fn raw(&self, name: &str) -> Result<Vec<u8>, UciPacketParseError> {
+        Ok(self.get(name)?.raw.clone())
+    }
+
+    fn aoa(&self, angle: &str, fom: &str) -> Result<Aoa, UciPacketParseError> {
+        Ok(Aoa { angle: self.fixed(angle)?, fom: self.num(fom)? })
+    }
+
+    fn session_id(&self) -> Result<u32, UciPacketParseError> {
+        num(self.any(&["SESSION_ID", "SESSION_HANDLE"])?)
+    }
+
+    /* the items of list `name`, none if it was not printed */
+    fn list<T: FromFields>(&self, name: &str) -> Result<Vec<T>, UciPacketParseError> {
+        match self.param(name) {
+            Some(p) => p.value.as_list().ok_or_else(|| invalid(p))?.iter().map(|item| T::from_fields(&Fields(item))).collect(),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn ids(&self) -> Vec<Vec<u8>> {
+        self.0.iter().filter(|p| p.name == "PARAMETER_ID").map(|p| p.raw.clone()).collect()
+    }
+
+    /* the configuration parameters following their count, if printed */
+    fn config(&self) -> Config {
+        let params = match self.0.iter().position(|p| p.name == "Number of parameters") {
+            Some(i) => self.0[i + 1..].to_vec(),
+            None => Vec::new(),
+        };
+        Config { params }
+    }
+}
+
+fn invalid(p: &Param) -> UciPacketParseError {
+    UciPacketParseError::new(&format!("unexpected {} value {}", p.name, p.value))
+}
+
+fn num<T: TryFrom<u32>>(p: &Param) -> Result<T, UciPacketParseError> {
+    p.value.as_u32().and_then(|v| T::try_from(v).ok()).ok_or_else(|| invalid(p))
+}
+
+fn code(p: &Param) -> Result<Code, UciPacketParseError> {
+    match p.value {
+        Value::Enum(value, name) => Ok(Code { value, name }),
+        _ => Err(invalid(p)),
+    }
+}
+
+/* little-endian integer of up to 8 bytes, e.g. a 40-bit timestamp */
+fn uint(p: &Param) -> Result<u64, UciPacketParseError> {
+    match p.raw.len() {
+        1..=8 => Ok(p.raw.iter().rev().fold(0u64, |v, b| v << 8 | *b as u64)),
+        _ => Err(invalid(p)),
+    }
+}
+
+fn version(p: &Param) -> Result<Version, UciPacketParseError> {
+    match p.raw[..] {
+        [major, minor] => Ok(Version { major, minor: minor >> 4, maintenance: minor & 0x0f }),
+        _ => Err(invalid(p)),
+    }
+}
+
+fn mac_address(f: &Fields) -> Result<MacAddress, UciPacketParseError> {
+    let p = f.get("Mac Address")?;
+    match p.raw.len() {
+        2 => Ok(MacAddress::Short(num(p)?)),
+        _ => Ok(MacAddress::Extended(uint(p)?)),
+    }
+}
+
+impl FromFields for Empty {
+    fn from_fields(_f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(Empty)
+    }
+}
+
+impl FromFields for StatusOnly {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(StatusOnly { status: f.code("STATUS")? })
+    }
+}
+
+impl FromFields for SessionCmd {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(SessionCmd { session_id: f.session_id()? })
+    }
+}
+
+impl FromFields for CoreDeviceStatusNtf {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(CoreDeviceStatusNtf { state: f.code("STATUS")?.value.into() })
+    }
+}
+
+impl FromFields for CoreGetDeviceInfoRsp {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(CoreGetDeviceInfoRsp {
+            status: f.code("STATUS")?,
+            uci_version: version(f.get("UCI_GENERIC_VERSION")?)?,
+            mac_version: version(f.get("MAC_VERSION")?)?,
+            phy_version: version(f.get("PHY_VERSION")?)?,
+            uci_test_version: version(f.get("UCI_TEST_VERSION")?)?,
+            vendor_info: f.raw("VENDOR_SPECIFIC_INFO")?,
+            vendor_config: f.config(),
+        })
+    }
+}
+
+impl FromFields for Config {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(f.config())
+    }
+}
+
+impl FromFields for ConfigRsp {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(ConfigRsp { status: f.code("STATUS")?, config: f.config() })
+    }
+}
+
+impl FromFields for ConfigStatus {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(ConfigStatus { id: f.raw("PARAMETER_ID")?, status: f.code("STATUS")? })
+    }
+}
+
+impl FromFields for SetConfigRsp {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(SetConfigRsp { status: f.code("STATUS")?, params: f.list("Parameter status")? })
+    }
+}
+
+impl FromFields for GetConfigCmd {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(GetConfigCmd { ids: f.ids() })
+    }
+}
+
+impl FromFields for SetAppConfigCmd {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(SetAppConfigCmd { session_id: f.session_id()?, config: f.config() })
+    }
+}
+
+impl FromFields for GetAppConfigCmd {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(GetAppConfigCmd { session_id: f.session_id()?, ids: f.ids() })
+    }
+}
+
+impl FromFields for SessionInitCmd {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(SessionInitCmd { session_id: f.num("SESSION_ID")?, session_type: f.code("SESSION_TYPE")? })
+    }
+}
+
+impl FromFields for SessionInitRsp {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(SessionInitRsp { status: f.code("STATUS")?, session_handle: f.opt("SESSION_HANDLE", num)? })
+    }
+}
+
+impl FromFields for SessionStatusNtf {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(SessionStatusNtf {
+            session_id: f.session_id()?,
+            state: f.code("SESSION_STATE")?.value.into(),
+            reason: f.code("REASON_CODE")?,
+        })
+    }
+}
+
+impl FromFields for SessionGetCountRsp {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(SessionGetCountRsp { status: f.code("STATUS")?, count: f.num("SESSION_COUNT")? })
+    }
+}
+
+impl FromFields for SessionGetStateRsp {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(SessionGetStateRsp { status: f.code("STATUS")?, state: f.code("SESSION_STATE")?.value.into() })
+    }
+}
+
+impl FromFields for Controlee {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(Controlee {
+            short_address: f.num("SHORT_ADDRESS")?,
+            sub_session_id: f.num("SUB_SESSION_ID")?,
+            sub_session_key: f.opt("SUB_SESSION_KEY", |p| Ok(p.raw.clone()))?,
+        })
+    }
+}
+
+impl FromFields for SessionUpdateControllerMulticastListCmd {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(SessionUpdateControllerMulticastListCmd {
+            session_id: f.session_id()?,
+            action: f.code("ACTION")?,
+            controlees: f.list("Controlees")?,
+        })
+    }
+}
+
+impl FromFields for ControleeStatus {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(ControleeStatus {
+            mac_address: f.num("MAC_ADDRESS")?,
+            sub_session_id: f.opt("SUB_SESSION_ID", num)?,
+            status: f.code("STATUS")?,
+        })
+    }
+}
+
+impl FromFields for SessionUpdateControllerMulticastListNtf {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(SessionUpdateControllerMulticastListNtf {
+            session_id: f.session_id()?,
+            remaining_multicast_list_size: f.opt("REMAINING_MULTICAST_LIST_SIZE", num)?,
+            controlees: f.list("Controlees")?,
+        })
+    }
+}
+
+impl FromFields for GetRangingCountRsp {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(GetRangingCountRsp { status: f.code("STATUS")?, count: f.opt("COUNT", num)? })
+    }
+}
+
+impl FromFields for RangeDataNtf {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        let reports = f.0.iter().position(|p| p.name == "Reports").ok_or_else(|| UciPacketParseError::new("missing Reports"))?;
+        Ok(RangeDataNtf {
+            sequence_number: f.num("Sequence number")?,
+            session_id: num(f.any(&["Session ID", "Session Handle"])?)?,
+            rcr_indication: f.opt("RCR indication", num)?,
+            ranging_interval: f.num("Ranging interval")?,
+            ranging_type: f.code("Ranging type")?,
+            mac_addressing_mode: f.code("Mac addressing mode")?,
+            measurements: f.list("Reports")?,
+            vendor: f.0[reports + 1..].to_vec(),
+        })
+    }
+}
+
+/* the kind of a measurement is told by its fields, as OWR for AoA may be reported as DL-TDoA */
+impl FromFields for Measurement {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        if f.param("Distance").is_some() {
+            TwrMeasurement::from_fields(f).map(Measurement::Twr)
+        } else if f.param("Frame Type").is_some() {
+            TdoaMeasurement::from_fields(f).map(Measurement::Tdoa)
+        } else if f.param("Message Type").is_some() {
+            DlTdoaMeasurement::from_fields(f).map(Measurement::DlTdoa)
+        } else {
+            OwrAoaMeasurement::from_fields(f).map(Measurement::OwrAoa)
+        }
+    }
+}
+
+impl FromFields for TwrMeasurement {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(TwrMeasurement {
+            mac_address: mac_address(f)?,
+            status: f.code("Status")?,
+            nlos: f.code("NLoS")?,
+            distance: f.num("Distance")?,
+            aoa_azimuth: f.aoa("AoA Azimuth", "AoA Azimuth FOM")?,
+            aoa_elevation: f.aoa("AoA Elevation", "AoA Elevation FOM")?,
+            aoa_destination_azimuth: f.aoa("AoA Destination Azimuth", "AoA Destination Azimuth FOMR")?,
+            aoa_destination_elevation: f.aoa("AoA Destination Elevation", "AoA Destination Elevation FOMR")?,
+            slot_index: f.num("Slot Index")?,
+            rssi: f.opt("RSSI", |p| p.value.as_f64().ok_or_else(|| invalid(p)))?,
+        })
+    }
+}
+
+impl FromFields for TdoaMeasurement {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(TdoaMeasurement {
+            mac_address: mac_address(f)?,
+            status: f.code("Status")?,
+            message_control: f.num("Message Control")?,
+            frame_type: f.code("Frame Type")?,
+            nlos: f.code("NLoS")?,
+            aoa_azimuth: f.aoa("AoA Azimuth", "AoA Azimuth FOM")?,
+            aoa_elevation: f.aoa("AoA Elevation", "AoA Elevation FOM")?,
+            frame_number: f.num("Frame Number")?,
+            rx_timestamp: uint(f.get("RX Timestamp")?)?,
+            device_id: f.opt("UL-TDoA Device ID", |p| Ok(p.raw.clone()))?,
+            tx_timestamp: f.opt("TX Timestamp", uint)?,
+        })
+    }
+}
+
+impl FromFields for DlTdoaMeasurement {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(DlTdoaMeasurement {
+            mac_address: mac_address(f)?,
+            status: f.code("Status")?,
+            message_type: f.code("Message Type")?,
+            message_control: f.num("Message Control")?,
+            block_index: f.num("Block Index")?,
+            round_index: f.num("Round Index")?,
+            nlos: f.code("NLoS")?,
+            aoa_azimuth: f.aoa("AoA Azimuth", "AoA Azimuth FOM")?,
+            aoa_elevation: f.aoa("AoA Elevation", "AoA Elevation FOM")?,
+            rssi: f.fixed("RSSI")?,
+            tx_timestamp: uint(f.get("TX Timestamp")?)?,
+            rx_timestamp: uint(f.get("RX Timestamp")?)?,
+            anchor_cfo: f.fixed("Anchor CFO")?,
+            cfo: f.fixed("CFO")?,
+            initiator_reply_time: f.num("Initiator Reply Time")?,
+            responder_reply_time: f.num("Responder Reply Time")?,
+            initiator_responder_tof: f.num("Initiator-Responder ToF")?,
+            anchor_location: f.opt("DT-Anchor Location", |p| Ok(p.raw.clone()))?,
+            active_ranging_rounds: f.opt("Active Ranging Rounds", |p| Ok(p.raw.clone()))?,
+        })
+    }
+}
+
+impl FromFields for OwrAoaMeasurement {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(OwrAoaMeasurement {
+            mac_address: mac_address(f)?,
+            status: f.code("Status")?,
+            nlos: f.code("NLoS")?,
+            frame_sequence_number: f.num("Frame Sequence Number")?,
+            block_index: f.num("Block Index")?,
+            aoa_azimuth: f.aoa("AoA Azimuth", "AoA Azimuth FOM")?,
+            aoa_elevation: f.aoa("AoA Elevation", "AoA Elevation FOM")?,
+        })
+    }
+}
+
+impl FromFields for RangeCccDataNtf {
+    fn from_fields(f: &Fields) -> Result<Self, UciPacketParseError> {
+        Ok(RangeCccDataNtf {
+            session_id: f.session_id()?,
+            ranging_status: f.code("RANGING_STATUS")?,
+            sts_index: f.num("STS_INDEX")?,
+            rr_index: f.num("RR_INDEX")?,
+            distance: f.num("DISTANCE")?,
+            uncertainty_anchor_fom: f.num("UNCERTAINTY_ANCHOR_FOM")?,
+            uncertainty_initiator_fom: f.num("UNCERTAINTY_INITIATOR_FOM")?,
+            ccm_tag: f.raw("CCM_TAG")?,
+            aoa_azimuth: f.aoa("AOA_AZIMUTH", "AOA_AZIMUTH_FOM")?,
+            aoa_elevation: f.aoa("AOA_ELEVATION", "AOA_ELEVATION_FOM")?,
+        })
+    }
+}
@@ -0,0 +1,463 @@
+use crate::uci::{Direction, FrameInfo, Packet};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/* LINKTYPE_FIRA_UCI: each frame is a UCI packet starting with its 4-byte header */
+pub const LINKTYPE_FIRA_UCI: u16 = 299;
+
+const PCAPNG_SHB: u32 = 0x0a0d0d0a;
+const PCAPNG_IDB: u32 = 0x00000001;
+const PCAPNG_SPB: u32 = 0x00000003;
+const PCAPNG_EPB: u32 = 0x00000006;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b3c4d;
+const PCAPNG_OPT_ENDOFOPT: u16 = 0;
+const PCAPNG_OPT_IF_TSRESOL: u16 = 9;
+const PCAPNG_OPT_EPB_FLAGS: u16 = 2;
+
+/* A UCI frame read from a capture */
+pub struct PcapFrame {
+    pub info: FrameInfo,
+    pub bytes: Vec<u8>,
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/*
+ * Calendar helpers, UTC only (days since 1970-01-01 <-> civil date).
+ * See http://howardhinnant.github.io/date_algorithms.html
+ */
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (m as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (if m <= 2 { yoe + era * 400 + 1 } else { yoe + era * 400 }, m, d)
+}
+
+fn now_us() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_micros() as u64).unwrap_or(0)
+}
+
+/* "YYYY-MM-DD hh:mm:ss.ffffff" in UTC */
+pub fn format_timestamp(us: u64) -> String {
+    let secs = us / 1_000_000;
+    let (y, m, d) = civil_from_days((secs / 86400) as i64);
+    let tod = secs % 86400;
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}", y, m, d, tod / 3600, tod / 60 % 60, tod % 60, us % 1_000_000)
+}
+
+/*
+ * Parses logcat timestamps, "MM-DD hh:mm:ss.fff" or "YYYY-MM-DD hh:mm:ss.fff",
+ * into microseconds since the epoch. Logcat has no year by default; the
+ * current one is assumed.
+ */
+pub fn parse_timestamp(s: &str) -> Option<u64> {
+    let mut tokens = s.split_whitespace();
+    let date: Vec<&str> = tokens.next()?.split('-').collect();
+    let time: Vec<&str> = tokens.next()?.split(':').collect();
+
+    let (y, m, d) = match date.as_slice() {
+        [y, m, d] => (y.parse().ok()?, m.parse().ok()?, d.parse().ok()?),
+        [m, d] => (civil_from_days((now_us() / 86_400_000_000) as i64).0, m.parse().ok()?, d.parse().ok()?),
+        _ => return None,
+    };
+    let (hh, mm, ss): (u64, u64, &str) = match time.as_slice() {
+        [hh, mm, ss] => (hh.parse().ok()?, mm.parse().ok()?, ss),
+        _ => return None,
+    };
+    let (sec, frac) = match ss.split_once('.') {
+        Some((sec, frac)) => (sec.parse::<u64>().ok()?, frac),
+        None => (ss.parse::<u64>().ok()?, ""),
+    };
+    /* fraction of a second, scaled to microseconds */
+    let frac: String = frac.chars().chain("000000".chars()).take(6).collect();
+    let us: u64 = frac.parse().ok()?;
+
+    let days = days_from_civil(y, m, d);
+    if days < 0 {
+        return None;
+    }
+    Some(((days as u64 * 86400 + hh * 3600 + mm * 60 + sec) * 1_000_000) + us)
+}
+
+fn read_u16(bytes: &[u8], big_endian: bool) -> u16 {
+    if big_endian { BigEndian::read_u16(bytes) } else { LittleEndian::read_u16(bytes) }
+}
+
+fn read_u32(bytes: &[u8], big_endian: bool) -> u32 {
+    if big_endian { BigEndian::read_u32(bytes) } else { LittleEndian::read_u32(bytes) }
+}
+
+fn frame_info(us: u64, direction: Option<Direction>) -> FrameInfo {
+    FrameInfo {
+        timestamp: Some(format_timestamp(us)),
+        direction,
+    }
+}
+
+/* classic libpcap file */
+fn read_pcap(data: &[u8]) -> io::Result<Vec<PcapFrame>> {
+    if data.len() < 24 {
+        return Err(invalid("truncated pcap header"));
+    }
+    let (big_endian, nanos) = match LittleEndian::read_u32(&data[0..4]) {
+        0xa1b2c3d4 => (false, false),
+        0xa1b23c4d => (false, true),
+        0xd4c3b2a1 => (true, false),
+        0x4d3cb2a1 => (true, true),
+        _ => return Err(invalid("not a pcap file")),
+    };
+    let linktype = read_u32(&data[20..24], big_endian) & 0xffff;
+    if linktype != u32::from(LINKTYPE_FIRA_UCI) {
+        return Err(invalid(&format!("unsupported link type {}", linktype)));
+    }
+
+    let mut frames = Vec::new();
+    let mut offset = 24;
+    while offset + 16 <= data.len() {
+        let sec = read_u32(&data[offset..], big_endian) as u64;
+        let frac = read_u32(&data[offset + 4..], big_endian) as u64;
+        let caplen = read_u32(&data[offset + 8..], big_endian) as usize;
+        offset += 16;
+        if offset + caplen > data.len() {
+            return Err(invalid("truncated pcap record"));
+        }
+        let us = sec * 1_000_000 + if nanos { frac / 1000 } else { frac };
+        frames.push(PcapFrame {
+            info: frame_info(us, None),
+            bytes: data[offset..offset + caplen].to_vec(),
+        });
+        offset += caplen;
+    }
+    Ok(frames)
+}
+
+struct Interface {
+    linktype: u16,
+    /* timestamp units per second */
+    resolution: u64,
+}
+
+/* iterates over the options of a block body */
+fn options(mut body: &[u8], big_endian: bool) -> Vec<(u16, &[u8])> {
+    let mut ret = Vec::new();
+    while body.len() >= 4 {
+        let code = read_u16(body, big_endian);
+        let len = read_u16(&body[2..], big_endian) as usize;
+        if code == PCAPNG_OPT_ENDOFOPT || 4 + len > body.len() {
+            break;
+        }
+        ret.push((code, &body[4..4 + len]));
+        body = body.get((4 + len + 3) & !3..).unwrap_or(&[]);
+    }
+    ret
+}
+
+fn read_pcapng(data: &[u8]) -> io::Result<Vec<PcapFrame>> {
+    let mut frames = Vec::new();
+    let mut interfaces: Vec<Interface> = Vec::new();
+    let mut big_endian = false;
+    let mut offset = 0;
+
+    while offset + 12 <= data.len() {
+        let block_type = read_u32(&data[offset..], big_endian);
+        if block_type == PCAPNG_SHB {
+            /* each section restarts byte order and interface numbering */
+            big_endian = match LittleEndian::read_u32(&data[offset + 8..]) {
+                PCAPNG_BYTE_ORDER_MAGIC => false,
+                0x4d3c2b1a => true,
+                _ => return Err(invalid("bad pcapng byte-order magic")),
+            };
+            interfaces.clear();
+        }
+        let block_len = read_u32(&data[offset + 4..], big_endian) as usize;
+        if block_len < 12 || offset + block_len > data.len() {
+            return Err(invalid("truncated pcapng block"));
+        }
+        let body = &data[offset + 8..offset + block_len - 4];
+
+        match block_type {
+            PCAPNG_IDB if body.len() >= 8 => {
+                let mut resolution = 1_000_000;
+                for (code, val) in options(&body[8..], big_endian) {
+                    if code == PCAPNG_OPT_IF_TSRESOL && !val.is_empty() {
+                        resolution = if val[0] & 0x80 != 0 {
+                            1u64.checked_shl((val[0] & 0x7f).into()).unwrap_or(1_000_000)
+                        } else {
+                            10u64.checked_pow(val[0].into()).unwrap_or(1_000_000)
+                        };
+                    }
+                }
+                interfaces.push(Interface {
+                    linktype: read_u16(body, big_endian),
+                    resolution,
+                });
+            }
+            PCAPNG_EPB if body.len() >= 20 => {
+                let iface = match interfaces.get(read_u32(body, big_endian) as usize) {
+                    Some(iface) => iface,
+                    None => return Err(invalid("packet refers to an unknown interface")),
+                };
+                let ts = ((read_u32(&body[4..], big_endian) as u64) << 32) | read_u32(&body[8..], big_endian) as u64;
+                let caplen = read_u32(&body[12..], big_endian) as usize;
+                if 20 + caplen > body.len() {
+                    return Err(invalid("truncated pcapng packet"));
+                }
+                let mut direction = None;
+                for (code, val) in options(body.get((20 + caplen + 3) & !3..).unwrap_or(&[]), big_endian) {
+                    if code == PCAPNG_OPT_EPB_FLAGS && val.len() >= 4 {
+                        direction = match read_u32(val, big_endian) & 0x3 {
+                            1 => Some(Direction::UwbsToHost),
+                            2 => Some(Direction::HostToUwbs),
+                            _ => None,
+                        };
+                    }
+                }
+                if iface.linktype == LINKTYPE_FIRA_UCI {
+                    let us = (ts as u128 * 1_000_000 / iface.resolution as u128) as u64;
+                    frames.push(PcapFrame {
+                        info: frame_info(us, direction),
+                        bytes: body[20..20 + caplen].to_vec(),
+                    });
+                }
+            }
+            /* simple packets belong to the first interface and carry no timestamp */
+            PCAPNG_SPB if body.len() >= 4 && interfaces.first().map(|i| i.linktype) == Some(LINKTYPE_FIRA_UCI) => {
+                let caplen = (read_u32(body, big_endian) as usize).min(body.len() - 4);
+                frames.push(PcapFrame {
+                    info: FrameInfo::default(),
+                    bytes: body[4..4 + caplen].to_vec(),
+                });
+            }
+            _ => (),
+        }
+        offset += block_len;
+    }
+
+    if !interfaces.iter().any(|i| i.linktype == LINKTYPE_FIRA_UCI) {
+        return Err(invalid(&format!("no interface with link type {}", LINKTYPE_FIRA_UCI)));
+    }
+    Ok(frames)
+}
+
+/* reads a pcap or pcapng capture, keeping only FiRa UCI frames */
+pub fn read(data: &[u8]) -> io::Result<Vec<PcapFrame>> {
+    if data.len() >= 4 && LittleEndian::read_u32(data) == PCAPNG_SHB {
+        read_pcapng(data)
+    } else {
+        read_pcap(data)
+    }
+}
+
+/* pcapng writer with a single FiRa UCI interface at microsecond resolution */
+pub struct PcapngWriter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> PcapngWriter<W> {
+    pub fn new(mut out: W) -> io::Result<PcapngWriter<W>> {
+        let mut shb = [0u8; 28];
+        LittleEndian::write_u32(&mut shb[0..], PCAPNG_SHB);
+        LittleEndian::write_u32(&mut shb[4..], 28);
+        LittleEndian::write_u32(&mut shb[8..], PCAPNG_BYTE_ORDER_MAGIC);
+        LittleEndian::write_u16(&mut shb[12..], 1);
+        LittleEndian::write_u16(&mut shb[14..], 0);
+        /* section length unknown */
+        LittleEndian::write_u64(&mut shb[16..], u64::MAX);
+        LittleEndian::write_u32(&mut shb[24..], 28);
+        out.write_all(&shb)?;
+
+        let mut idb = [0u8; 20];
+        LittleEndian::write_u32(&mut idb[0..], PCAPNG_IDB);
+        LittleEndian::write_u32(&mut idb[4..], 20);
+        LittleEndian::write_u16(&mut idb[8..], LINKTYPE_FIRA_UCI);
+        LittleEndian::write_u32(&mut idb[12..], 0);
+        LittleEndian::write_u32(&mut idb[16..], 20);
+        out.write_all(&idb)?;
+
+        Ok(PcapngWriter { out })
+    }
+
+    /* `us`: microseconds since the epoch */
+    pub fn write_packet(&mut self, bytes: &[u8], us: u64, direction: Option<Direction>) -> io::Result<()> {
+        let padded = (bytes.len() + 3) & !3;
+        let opts = if direction.is_some() { 12 } else { 0 };
+        let block_len = 32 + padded + opts;
+
+        let mut block = vec![0u8; block_len];
+        LittleEndian::write_u32(&mut block[0..], PCAPNG_EPB);
+        LittleEndian::write_u32(&mut block[4..], block_len as u32);
+        LittleEndian::write_u32(&mut block[8..], 0);
+        LittleEndian::write_u32(&mut block[12..], (us >> 32) as u32);
+        LittleEndian::write_u32(&mut block[16..], us as u32);
+        LittleEndian::write_u32(&mut block[20..], bytes.len() as u32);
+        LittleEndian::write_u32(&mut block[24..], bytes.len() as u32);
+        block[28..28 + bytes.len()].copy_from_slice(bytes);
+        if let Some(dir) = direction {
+            let opt = &mut block[28 + padded..];
+            LittleEndian::write_u16(&mut opt[0..], PCAPNG_OPT_EPB_FLAGS);
+            LittleEndian::write_u16(&mut opt[2..], 4);
+            /* inbound = received by the host */
+            LittleEndian::write_u32(&mut opt[4..], match dir { Direction::UwbsToHost => 1, Direction::HostToUwbs => 2 });
+            /* opt_endofopt is left zeroed */
+        }
+        LittleEndian::write_u32(&mut block[block_len - 4..], block_len as u32);
+        self.out.write_all(&block)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+/*
+ * Writes frames to a pcapng capture. Frames without a usable timestamp get
+ * the conversion time, 1 ms apart; a missing direction is taken from the MT.
+ */
+pub fn export<W: Write>(out: W, frames: &[(Packet, FrameInfo)]) -> io::Result<()> {
+    let mut writer = PcapngWriter::new(out)?;
+    let start = now_us();
+    for (i, (pkt, info)) in frames.iter().enumerate() {
+        let us = info.timestamp.as_deref().and_then(parse_timestamp).unwrap_or(start + i as u64 * 1000);
+        writer.write_packet(pkt.bytes(), us, Some(info.direction.unwrap_or_else(|| Direction::of(pkt))))?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CMD: [u8; 8] = [0x21, 0x00, 0x00, 0x04, 0x01, 0x00, 0x00, 0x00];
+    const NTF: [u8; 5] = [0x60, 0x01, 0x00, 0x01, 0x01];
+
+    /* 2026-10-16 22:48:20.123456 UTC */
+    const TS_US: u64 = 1_792_190_900_123_456;
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(format_timestamp(TS_US), "2026-10-16 22:48:20.123456");
+        assert_eq!(parse_timestamp("2026-10-16 22:48:20.123456"), Some(TS_US));
+        assert_eq!(parse_timestamp("2026-10-16 22:48:20.123"), Some(TS_US - 456));
+        assert_eq!(parse_timestamp("10-16 22:48"), None);
+    }
+
+    #[test]
+    fn export_round_trip() {
+        let frames = vec![
+            (Packet::from_bytes(&CMD).unwrap(), FrameInfo {
+                timestamp: Some("2026-10-16 22:48:20.123456".to_string()),
+                direction: Some(Direction::HostToUwbs),
+            }),
+            /* direction from the MT */
+            (Packet::from_bytes(&NTF).unwrap(), FrameInfo {
+                timestamp: Some("2026-10-16 22:48:21.000001".to_string()),
+                direction: None,
+            }),
+        ];
+        let mut out = Vec::new();
+        export(&mut out, &frames).unwrap();
+
+        let read = read(&out).unwrap();
+        assert_eq!(read.len(), 2);
+        assert_eq!(read[0].bytes, CMD);
+        assert_eq!(read[0].info.timestamp.as_deref(), Some("2026-10-16 22:48:20.123456"));
+        assert_eq!(read[0].info.direction, Some(Direction::HostToUwbs));
+        /* the 5-byte frame is padded in the block */
+        assert_eq!(read[1].bytes, NTF);
+        assert_eq!(read[1].info.timestamp.as_deref(), Some("2026-10-16 22:48:21.000001"));
+        assert_eq!(read[1].info.direction, Some(Direction::UwbsToHost));
+    }
+
+    fn put_u32(out: &mut Vec<u8>, v: u32, big_endian: bool) {
+        out.extend_from_slice(&if big_endian { v.to_be_bytes() } else { v.to_le_bytes() });
+    }
+
+    /* classic pcap with one record per frame, 1 s apart */
+    fn pcap(big_endian: bool, nanos: bool, linktype: u32, frames: &[&[u8]]) -> Vec<u8> {
+        let mut out = Vec::new();
+        put_u32(&mut out, if nanos { 0xa1b23c4d } else { 0xa1b2c3d4 }, big_endian);
+        out.extend_from_slice(&if big_endian { [0, 2, 0, 4] } else { [2, 0, 4, 0] });
+        put_u32(&mut out, 0, big_endian);
+        put_u32(&mut out, 0, big_endian);
+        put_u32(&mut out, 65535, big_endian);
+        put_u32(&mut out, linktype, big_endian);
+        for (i, frame) in frames.iter().enumerate() {
+            put_u32(&mut out, 1_792_190_900 + i as u32, big_endian);
+            put_u32(&mut out, if nanos { 123_456_789 } else { 123_456 }, big_endian);
+            put_u32(&mut out, frame.len() as u32, big_endian);
+            put_u32(&mut out, frame.len() as u32, big_endian);
+            out.extend_from_slice(frame);
+        }
+        out
+    }
+
+    #[test]
+    fn classic_pcap() {
+        for big_endian in [false, true] {
+            for nanos in [false, true] {
+                let frames = read(&pcap(big_endian, nanos, 299, &[&CMD, &NTF])).unwrap();
+                assert_eq!(frames.len(), 2);
+                assert_eq!(frames[0].bytes, CMD);
+                assert_eq!(frames[1].bytes, NTF);
+                assert_eq!(frames[0].info.timestamp.as_deref(), Some("2026-10-16 22:48:20.123456"));
+                assert_eq!(frames[1].info.timestamp.as_deref(), Some("2026-10-16 22:48:21.123456"));
+                assert_eq!(frames[0].info.direction, None);
+            }
+        }
+    }
+
+    #[test]
+    fn classic_pcap_errors() {
+        assert!(read(&pcap(false, false, 1, &[&CMD])).err().unwrap().to_string().contains("unsupported link type 1"));
+        assert!(read(&[0u8; 24]).is_err());
+        let mut truncated = pcap(true, false, 299, &[&CMD]);
+        truncated.pop();
+        assert!(read(&truncated).err().unwrap().to_string().contains("truncated pcap record"));
+    }
+
+    /* big-endian pcapng, nanosecond resolution, one inbound packet */
+    #[test]
+    fn pcapng_big_endian() {
+        let mut out = Vec::new();
+        for v in [PCAPNG_SHB, 28, PCAPNG_BYTE_ORDER_MAGIC, 0x0001_0000, 0xffff_ffff, 0xffff_ffff, 28] {
+            put_u32(&mut out, v, true);
+        }
+        /* IDB with if_tsresol = 9 */
+        for v in [PCAPNG_IDB, 28, 299 << 16, 0, 0x0009_0001, 0x0900_0000, 28] {
+            put_u32(&mut out, v, true);
+        }
+        let ns = TS_US * 1000 + 789;
+        for v in [PCAPNG_EPB, 52, 0, (ns >> 32) as u32, ns as u32, 5, 5] {
+            put_u32(&mut out, v, true);
+        }
+        out.extend_from_slice(&NTF);
+        out.extend_from_slice(&[0, 0, 0]);
+        for v in [0x0002_0004, 1, 0, 52] {
+            put_u32(&mut out, v, true);
+        }
+
+        let frames = read(&out).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].bytes, NTF);
+        assert_eq!(frames[0].info.timestamp.as_deref(), Some("2026-10-16 22:48:20.123456"));
+        assert_eq!(frames[0].info.direction, Some(Direction::UwbsToHost));
+    }
+}
@@ -0,0 +1,310 @@
+use crate::message::{Param, Value};
+use std::cell::RefCell;
+
+/*
+ * Output sink for decoders. Decoders emit fields in payload order; repeated
+ * structures are bracketed by begin_list()/end_list() with one
+ * begin_item()/end_item() pair per entry.
+ */
+pub trait Printer {
+    fn print_id(&self, name: &str);
+    fn print_comment(&self, s: &str);
+    fn print_param(&self, name: &str, val: &str);
+
+    /* typed field read from `raw` at payload offset `offset` */
+    fn print_value(&self, name: &str, _offset: usize, _raw: &[u8], val: &Value) {
+        self.print_param(name, &val.to_string());
+    }
+    fn begin_list(&self, _name: &str) {}
+    fn begin_item(&self, title: &str) {
+        self.print_comment(title);
+    }
+    fn end_item(&self) {}
+    fn end_list(&self) {}
+}
+
+pub struct BasicPrinter;
+
+impl Printer for BasicPrinter {
+    fn print_id(&self, name: &str) {
+        println!("{}", name);
+    }
+    fn print_comment(&self, s: &str) {
+        println!("{}", s);
+    }
+    fn print_param(&self, name: &str, val: &str) {
+        println!("- {} = {}", name, val);
+    }
+}
+
+enum Frame {
+    Params(Vec<Param>),
+    List(String, Vec<Vec<Param>>),
+}
+
+/* Builds the typed parameter tree instead of printing */
+pub(crate) struct Collector {
+    stack: RefCell<Vec<Frame>>,
+    comments: RefCell<Vec<String>>,
+}
+
+impl Collector {
+    pub(crate) fn new() -> Collector {
+        Collector {
+            stack: RefCell::new(vec![Frame::Params(Vec::new())]),
+            comments: RefCell::new(Vec::new()),
+        }
+    }
+
+    fn push_param(&self, param: Param) {
+        if let Some(Frame::Params(params)) = self.stack.borrow_mut().last_mut() {
+            params.push(param);
+        }
+    }
+
+    pub(crate) fn into_parts(self) -> (Vec<Param>, Vec<String>) {
+        let params = match self.stack.into_inner().into_iter().next() {
+            Some(Frame::Params(params)) => params,
+            _ => Vec::new(),
+        };
+        (params, self.comments.into_inner())
+    }
+}
+
+impl Printer for Collector {
+    fn print_id(&self, _name: &str) {}
+    fn print_comment(&self, s: &str) {
+        self.comments.borrow_mut().push(s.to_string());
+    }
+    fn print_param(&self, name: &str, val: &str) {
+        self.push_param(Param {
+            name: name.to_string(),
+            offset: None,
+            raw: Vec::new(),
+            value: Value::Text(val.to_string()),
+        });
+    }
+    fn print_value(&self, name: &str, offset: usize, raw: &[u8], val: &Value) {
+        self.push_param(Param {
+            name: name.to_string(),
+            offset: Some(offset),
+            raw: raw.to_vec(),
+            value: val.clone(),
+        });
+    }
+    fn begin_list(&self, name: &str) {
+        self.stack.borrow_mut().push(Frame::List(name.to_string(), Vec::new()));
+    }
+    fn begin_item(&self, _title: &str) {
+        self.stack.borrow_mut().push(Frame::Params(Vec::new()));
+    }
+    fn end_item(&self) {
+        let mut stack = self.stack.borrow_mut();
+        if let Some(Frame::Params(item)) = stack.pop() {
+            if let Some(Frame::List(_, items)) = stack.last_mut() {
+                items.push(item);
+            }
+        }
+    }
+    fn end_list(&self) {
+        let frame = self.stack.borrow_mut().pop();
+        if let Some(Frame::List(name, items)) = frame {
+            self.push_param(Param {
+                name,
+                offset: None,
+                raw: Vec::new(),
+                value: Value::List(items),
+            });
+        }
+    }
+}
+
+fn json_str(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+enum JsonFrame {
+    Fields(Vec<String>),
+    List(String, Vec<String>),
+    Item(String, Vec<String>),
+}
+
+/*
+ * Emits one JSON object per packet:
+ * {"mt", "gid", "oid" (or "dpf"), "name", "fields": [{"name", "offset", "raw", "value"} | {"name", "items": [{"title", "fields"}]}], "comments", "error"}
+ */
+pub struct JsonPrinter {
+    header: (u8, u8, u8),
+    meta: Vec<(String, String)>,
+    name: RefCell<Option<String>>,
+    stack: RefCell<Vec<JsonFrame>>,
+    comments: RefCell<Vec<String>>,
+}
+
+impl JsonPrinter {
+    pub fn new(mt: u8, gid: u8, oid: u8) -> JsonPrinter {
+        JsonPrinter {
+            header: (mt, gid, oid),
+            meta: Vec::new(),
+            name: RefCell::new(None),
+            stack: RefCell::new(vec![JsonFrame::Fields(Vec::new())]),
+            comments: RefCell::new(Vec::new()),
+        }
+    }
+
+    /* extra string member emitted before the packet fields, e.g. a capture timestamp */
+    pub fn set_meta(&mut self, key: &str, value: &str) {
+        self.meta.push((key.to_string(), value.to_string()));
+    }
+
+    /* a line that is not a decoded packet, e.g. an input error */
+    pub fn message(meta: &[(&str, &str)], key: &str, msg: &str) -> String {
+        let meta: String = meta.iter().map(|(k, v)| format!("{}:{},", json_str(k), json_str(v))).collect();
+        format!("{{{}{}:{}}}", meta, json_str(key), json_str(msg))
+    }
+
+    fn push_field(&self, field: String) {
+        match self.stack.borrow_mut().last_mut() {
+            Some(JsonFrame::Fields(fields)) | Some(JsonFrame::Item(_, fields)) => fields.push(field),
+            _ => (),
+        }
+    }
+
+    /* the packet object, including the decoding error if any */
+    pub fn finish(&self, error: Option<&str>) -> String {
+        /* close lists left open by a failed decoder */
+        while self.stack.borrow().len() > 1 {
+            if matches!(self.stack.borrow().last(), Some(JsonFrame::Item(..))) {
+                self.end_item();
+            } else {
+                self.end_list();
+            }
+        }
+        let fields = match self.stack.borrow().last() {
+            Some(JsonFrame::Fields(fields)) => fields.join(","),
+            _ => String::new(),
+        };
+
+        /* data packets (MT=0) carry a DPF instead of GID/OID */
+        let id = match self.header {
+            (0, dpf, _) => format!("\"dpf\":{}", dpf),
+            (_, gid, oid) => format!("\"gid\":{},\"oid\":{}", gid, oid),
+        };
+        let meta: String = self.meta.iter().map(|(k, v)| format!("{}:{},", json_str(k), json_str(v))).collect();
+        let mut out = format!("{{{}\"mt\":{},{},\"name\":{},\"fields\":[{}]",
+                              meta, self.header.0, id,
+                              match &*self.name.borrow() { Some(n) => json_str(n), None => "null".to_string() },
+                              fields);
+        let comments = self.comments.borrow();
+        if !comments.is_empty() {
+            out += &format!(",\"comments\":[{}]", comments.iter().map(|c| json_str(c)).collect::<Vec<String>>().join(","));
+        }
+        if let Some(e) = error {
+            out += &format!(",\"error\":{}", json_str(e));
+        }
+        out + "}"
+    }
+}
+
+impl Printer for JsonPrinter {
+    fn print_id(&self, name: &str) {
+        *self.name.borrow_mut() = Some(name.to_string());
+    }
+    fn print_comment(&self, s: &str) {
+        self.comments.borrow_mut().push(s.to_string());
+    }
+    fn print_param(&self, name: &str, val: &str) {
+        self.push_field(format!("{{\"name\":{},\"value\":{}}}", json_str(name), json_str(val)));
+    }
+    fn print_value(&self, name: &str, offset: usize, raw: &[u8], val: &Value) {
+        let raw: String = raw.iter().map(|b| format!("{:02x}", b)).collect();
+        self.push_field(format!("{{\"name\":{},\"offset\":{},\"raw\":\"{}\",\"value\":{}}}",
+                                json_str(name), offset, raw, json_str(&val.to_string())));
+    }
+    fn begin_list(&self, name: &str) {
+        self.stack.borrow_mut().push(JsonFrame::List(name.to_string(), Vec::new()));
+    }
+    fn begin_item(&self, title: &str) {
+        self.stack.borrow_mut().push(JsonFrame::Item(title.to_string(), Vec::new()));
+    }
+    fn end_item(&self) {
+        let mut stack = self.stack.borrow_mut();
+        if let Some(JsonFrame::Item(title, fields)) = stack.pop() {
+            if let Some(JsonFrame::List(_, items)) = stack.last_mut() {
+                items.push(format!("{{\"title\":{},\"fields\":[{}]}}", json_str(&title), fields.join(",")));
+            }
+        }
+    }
+    fn end_list(&self) {
+        let frame = self.stack.borrow_mut().pop();
+        if let Some(JsonFrame::List(name, items)) = frame {
+            self.push_field(format!("{{\"name\":{},\"items\":[{}]}}", json_str(&name), items.join(",")));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_shape() {
+        let mut printer = JsonPrinter::new(3, 1, 2);
+        printer.set_meta("direction", "UWBS -> Host");
+        printer.print_id("SESSION_STATUS_NTF");
+        printer.print_value("SESSION_ID", 0, &[0x01, 0x00, 0x00, 0x00], &Value::Hex(1));
+        printer.begin_list("Items");
+        printer.begin_item("Item 0");
+        printer.print_value("STATE", 4, &[0x02], &Value::Enum(2, Some("IDLE")));
+        printer.end_item();
+        printer.end_list();
+        printer.print_param("Note", "a \"quoted\"\nline");
+        printer.print_comment("a comment");
+
+        let json: serde_json::Value = serde_json::from_str(&printer.finish(None)).unwrap();
+        assert_eq!(json, serde_json::json!({
+            "direction": "UWBS -> Host",
+            "mt": 3, "gid": 1, "oid": 2,
+            "name": "SESSION_STATUS_NTF",
+            "fields": [
+                {"name": "SESSION_ID", "offset": 0, "raw": "01000000", "value": "0x1"},
+                {"name": "Items", "items": [
+                    {"title": "Item 0", "fields": [{"name": "STATE", "offset": 4, "raw": "02", "value": "0x02 (IDLE)"}]},
+                ]},
+                {"name": "Note", "value": "a \"quoted\"\nline"},
+            ],
+            "comments": ["a comment"],
+        }));
+    }
+
+    #[test]
+    fn json_data_packet_and_error() {
+        let printer = JsonPrinter::new(0, 1, 0);
+        /* a failed decoder may leave a list open */
+        printer.begin_list("Items");
+        printer.begin_item("Item 0");
+        let json: serde_json::Value = serde_json::from_str(&printer.finish(Some("length mismatch"))).unwrap();
+        assert_eq!(json["dpf"], 1);
+        assert!(json.get("gid").is_none());
+        assert_eq!(json["name"], serde_json::Value::Null);
+        assert_eq!(json["fields"], serde_json::json!([{"name": "Items", "items": [{"title": "Item 0", "fields": []}]}]));
+        assert_eq!(json["error"], "length mismatch");
+
+        let line = JsonPrinter::message(&[("timestamp", "t")], "error", "bad \\ input");
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json, serde_json::json!({"timestamp": "t", "error": "bad \\ input"}));
+    }
+}
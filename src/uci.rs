@@ -1,3 +1,5 @@
+use crate::message::*;
+use crate::printer::{BasicPrinter, Collector, JsonPrinter, Printer};
 use byteorder::{ByteOrder, LittleEndian};
 use lazy_static::lazy_static;
 use std::collections::HashMap;
@@ -5,47 +7,113 @@ use std::fmt;
 use std::num::ParseIntError;
 
 /* Packet type */
-struct Packet{
+#[derive(Debug, Clone)]
+pub struct Packet{
     bytes: Vec<u8>,
 }
 
 impl Packet {
     fn new(bytes: Vec<u8>) -> Packet {
         Packet {
-            bytes: bytes,
+            bytes,
         }
     }
-    fn mt(&self) -> u8 {
+
+    /* validates the header against the payload length */
+    pub fn from_bytes(bytes: &[u8]) -> Result<Packet, UciPacketParseError> {
+        if bytes.len() < 4 {
+            return Err(UciPacketParseError::new("packet length is less than 4 bytes"))
+        }
+
+        /* data packets have a 16-bit payload length */
+        let packet_len: usize = if bytes[0] >> 5 == mt::DATA {
+            LittleEndian::read_u16(&bytes[2..4]).into()
+        } else {
+            bytes[3].into()
+        };
+        if (bytes.len() - 4) != packet_len {
+            return Err(UciPacketParseError::new(format!("payload length mismatch: packet_len={} actual={}", packet_len, bytes.len() - 4).as_str()));
+        }
+
+        Ok(Packet::new(bytes.to_vec()))
+    }
+    /*
+     * header for `payload`; PBF is set on all but the last segment. Only a
+     * reassembled control message exceeds 255 bytes: byte 2 then holds the
+     * high byte of its length.
+     */
+    pub(crate) fn build(id: &PacketId, pbf: bool, payload: &[u8]) -> Packet {
+        let mut bytes = if id.2 == mt::DATA {
+            let mut hdr = vec![id.2 << 5 | id.0, 0, 0, 0];
+            LittleEndian::write_u16(&mut hdr[2..4], payload.len() as u16);
+            hdr
+        } else {
+            vec![id.2 << 5 | id.0, id.1, (payload.len() >> 8) as u8, payload.len() as u8]
+        };
+        if pbf {
+            bytes[0] |= 0x10;
+        }
+        bytes.extend_from_slice(payload);
+        Packet::new(bytes)
+    }
+
+    /* from a hex string such as "4100000100" */
+    pub fn from_hex(s: &str) -> Result<Packet, UciPacketParseError> {
+        to_packet(s)
+    }
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+    pub fn mt(&self) -> u8 {
         self.bytes[0] >> 5
     }
-    fn gid(&self) -> u8 {
+    pub fn pbf(&self) -> bool {
+        self.bytes[0] & 0x10 != 0
+    }
+    pub fn gid(&self) -> u8 {
         self.bytes[0] & 0xf
     }
-    fn oid(&self) -> u8 {
+    /* data packets carry the DPF where control packets carry the GID */
+    pub fn dpf(&self) -> u8 {
+        self.bytes[0] & 0xf
+    }
+    pub fn oid(&self) -> u8 {
         self.bytes[1]
     }
-    fn len(&self) -> u8 {
-        self.bytes[3]
+    /* payload length; may exceed 255 once segments are reassembled */
+    pub fn len(&self) -> usize {
+        self.bytes.len() - 4
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
     /* get(), slice(): for payload accesses */
-    fn get(&self, idx: usize) -> u8 {
+    pub fn get(&self, idx: usize) -> u8 {
         self.bytes[idx + 4]
     }
-    fn slice<'a>(&'a self, idx: usize, len: usize) -> &'a [u8] {
+    pub fn slice(&self, idx: usize, len: usize) -> &[u8] {
         &self.bytes[idx + 4..idx + len + 4]
     }
+    pub fn payload(&self) -> &[u8] {
+        &self.bytes[4..]
+    }
 }
 
 impl fmt::Display for Packet {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "mt:{:#3x} gid:{:#3x} oid:{:#3x} len:{:#4x}",
-               self.mt(), self.gid(), self.oid(), self.len())
+        if self.mt() == mt::DATA {
+            write!(f, "mt:{:#3x} dpf:{:#3x} len:{:#4x}",
+                   self.mt(), self.dpf(), self.len())
+        } else {
+            write!(f, "mt:{:#3x} gid:{:#3x} oid:{:#3x} len:{:#4x}",
+                   self.mt(), self.gid(), self.oid(), self.len())
+        }
     }
 }
 
-#[derive(Eq, PartialEq, Hash)]
-struct PacketId(u8, u8, u8);
+#[derive(Eq, PartialEq, Hash, Clone, Copy, Debug)]
+pub(crate) struct PacketId(pub(crate) u8, pub(crate) u8, pub(crate) u8);
 
 impl From<(u8, u8, u8)> for PacketId {
     fn from(v: (u8, u8, u8)) -> Self {
@@ -59,13 +127,24 @@ impl PartialEq<(u8, u8, u8)> for PacketId {
     }
 }
 
+impl From<&Packet> for PacketId {
+    fn from(pkt: &Packet) -> Self {
+        if pkt.mt() == mt::DATA {
+            PacketId(pkt.dpf(), 0, mt::DATA)
+        } else {
+            PacketId(pkt.gid(), pkt.oid(), pkt.mt())
+        }
+    }
+}
+
 /* Error types */
+#[derive(Debug)]
 pub struct UciPacketParseError {
     msg: String,
 }
 
 impl UciPacketParseError {
-    fn new(msg: &str) -> UciPacketParseError {
+    pub(crate) fn new(msg: &str) -> UciPacketParseError {
         UciPacketParseError { msg: msg.to_string() }
     }
 }
@@ -76,15 +155,23 @@ impl fmt::Display for UciPacketParseError {
     }
 }
 
+impl std::error::Error for UciPacketParseError {}
+
 /*
  * UCI protocol definitions
  */
-mod mt {
+pub(crate) mod mt {
+    pub const DATA  :u8 = 0;
     pub const CMD   :u8 = 1;
     pub const RSP   :u8 = 2;
     pub const NTF   :u8 = 3;
 }
 
+mod dpf {
+    pub const DATA_MESSAGE_SND          :u8 = 0x01;
+    pub const DATA_MESSAGE_RCV          :u8 = 0x02;
+}
+
 #[allow(dead_code)]
 mod gid {
     pub const CORE                      :u8 = 0x00;
@@ -92,6 +179,8 @@ mod gid {
     pub const RANGING                   :u8 = 0x02;
     pub const PROPRIETARY               :u8 = 0x0e;
     pub const PROPRIETARY_A             :u8 = 0x0a;
+    pub const PROPRIETARY_B             :u8 = 0x0b;
+    pub const PROPRIETARY_F             :u8 = 0x0f;
     pub const TEST                      :u8 = 0x0d;
 }
 
@@ -112,12 +201,14 @@ mod oid {
     pub const SESSION_GET_APP_CONFIG    :u8 = 4;
     pub const SESSION_GET_COUNT         :u8 = 5;
     pub const SESSION_GET_STATE         :u8 = 6;
+    pub const SESSION_UPDATE_CONTROLLER_MULTICAST_LIST :u8 = 7;
 
     pub const RANGE_START               :u8 = 0;
     pub const RANGE_DATA                :u8 = 0;
     pub const RANGE_STOP                :u8 = 1;
     pub const GET_RANGING_COUNT         :u8 = 3;
     pub const BLINK_DATA_TX             :u8 = 4;
+    pub const RANGE_CCC_DATA            :u8 = 0x20;
 
     pub const TEST_CONFIG_SET           :u8 = 0;
     pub const TEST_CONFIG_GET           :u8 = 1;
@@ -141,6 +232,9 @@ mod oid {
     pub const NXP_GET_INTF_STAT         :u8 = 0x21;
     pub const NXP_WRITE_CALIB_DATA      :u8 = 0;
     pub const NXP_READ_CALIB_DATA       :u8 = 1;
+    pub const NXP_DBG_LOG_CONFIG        :u8 = 0;
+    pub const NXP_CIR_CAPTURE           :u8 = 1;
+    pub const NXP_THERMAL_STATUS        :u8 = 0;
 }
 
 mod range_data {
@@ -154,7 +248,7 @@ mod range_data {
     impl From<u8> for MacType {
         fn from(v: u8) -> Self {
             match v {
-                2 => MacType::Short,
+                0 => MacType::Short,
                 1 => MacType::Long,
                 _ => MacType::Unknown,
             }
@@ -167,6 +261,7 @@ mod range_data {
         Tdoa,
         Twr,
         DownTdoa,
+        OwrAoa,
         Unknown,
     }
     impl From<u8> for ReportType {
@@ -175,22 +270,33 @@ mod range_data {
                 0 => ReportType::Tdoa,
                 1 => ReportType::Twr,
                 2 => ReportType::DownTdoa,
+                3 => ReportType::OwrAoa,
                 _ => ReportType::Unknown,
             }
         }
     }
 }
 
-#[allow(dead_code)]
-#[derive(PartialEq)]
-enum ParamType {
+#[allow(clippy::upper_case_acronyms)]
+#[derive(PartialEq, Clone, Copy)]
+pub(crate) enum ParamType {
     Hex8,
     Hex16,
     Hex32,
     Dec8,
     Dec16,
     Dec32,
-    Q16(usize), /* e.g. Q16(7) == Q9.7 */
+    Int8,
+    Int16,
+    Int32,
+    Q16(usize), /* signed, e.g. Q16(7) == Q9.7 */
+    Q8(usize),  /* signed, e.g. Q8(1) == Q7.1 */
+    Angle(usize), /* signed Q16, in degrees */
+    Version,    /* UCI version: major, minor << 4 | maintenance */
+    FwVersion,  /* NXP firmware version: major, minor, patch */
+    VersionRange, /* lowest and highest supported major.minor */
+    Bitmask(&'static [&'static str]), /* little-endian, up to 4 bytes, bit n named by entry n */
+    Entries(&'static [Field]), /* number of entries, then the entries; a last field of size 0 takes the rest of each entry */
     RFU(usize),
     HexArray(i16),
     CharArray(u16),
@@ -198,16 +304,25 @@ enum ParamType {
     Map8(&'static HashMap<u8, &'static str>),
 }
 
-#[derive(PartialEq)]
-struct Field(&'static str, ParamType);
+#[derive(PartialEq, Clone, Copy)]
+pub(crate) struct Field(pub(crate) &'static str, pub(crate) ParamType);
 
 impl Field {
-    fn size(&self) -> usize {
+    pub(crate) fn size(&self) -> usize {
         match self.1 {
             ParamType::Hex8  | ParamType::Dec8  => 1,
             ParamType::Hex16 | ParamType::Dec16 => 2,
             ParamType::Hex32 | ParamType::Dec32 => 4,
-            ParamType::Q16(_) => 2,
+            ParamType::Int8 => 1,
+            ParamType::Int16 => 2,
+            ParamType::Int32 => 4,
+            ParamType::Q16(_) | ParamType::Angle(_) => 2,
+            ParamType::Q8(_) => 1,
+            ParamType::Version => 2,
+            ParamType::FwVersion => 3,
+            ParamType::VersionRange => 4,
+            ParamType::Bitmask(_) => 0,
+            ParamType::Entries(_) => 0,
             ParamType::RFU(n) => n,
             ParamType::HexArray(x) => x as usize,
             ParamType::CharArray(x) => x as usize,
@@ -218,13 +333,27 @@ impl Field {
     fn length_compatible(&self, len: usize) -> bool {
         match self.size() {
             0 => true,
-            x => x as usize == len,
+            x => x == len,
         }
     }
 }
 
+/* enables the NXP block at the end of RANGE_DATA_NTF */
+const NXP_EXTENDED_NTF_CONFIG: (u8, u8) = (0xe4, 0x33);
+
+/* per-entry layouts of NXP calibration parameters */
+static RSSI_CALIB_ENTRY: [Field; 3] = [
+    Field("RX_ANTENNA_ID", ParamType::Hex8),
+    Field("RSSI_CALIB_CONSTANT1", ParamType::Int16),
+    Field("RSSI_CALIB_CONSTANT2", ParamType::Int16),
+];
+static MULTIPOINT_PDOA_ENTRY: [Field; 2] = [
+    Field("RX_ANTENNA_PAIR_ID", ParamType::Hex8),
+    Field("PDOA_VALUES", ParamType::HexArray(0)),
+];
+
 lazy_static! {
-    static ref STATUS_CODES: HashMap<u8, &'static str> = {
+    pub(crate) static ref STATUS_CODES: HashMap<u8, &'static str> = {
         HashMap::from([
             ( 0x00u8, "OK"),
             ( 0x01u8, "REJECTED"),
@@ -294,14 +423,14 @@ lazy_static! {
         ])
     };
 
-    static ref DEVICE_CONF_PARAMS: HashMap<u8, Field> = {
+    pub(crate) static ref DEVICE_CONF_PARAMS: HashMap<u8, Field> = {
         HashMap::from([
             ( 0x00u8, Field("DEVICE_STATE", ParamType::Hex8) ),
             ( 0x01u8, Field("LOW_POWER_MODE", ParamType::Hex8) ),
         ])
     };
 
-    static ref DEVICE_CONF_PARAMS_NXP: HashMap<(u8, u8), Field> = {
+    pub(crate) static ref DEVICE_CONF_PARAMS_NXP: HashMap<(u8, u8), Field> = {
         HashMap::from([
             ( (0xe4u8, 0x02u8), Field("DPD_WAKEUP_SRC", ParamType::Hex8) ),
             ( (0xe4u8, 0x03u8), Field("WTX_COUNT_CONFIG", ParamType::Dec8) ),
@@ -311,7 +440,7 @@ lazy_static! {
             ( (0xe4u8, 0x27u8), Field("DDFS_TONE_CONFIG", ParamType::HexArray(72)) ),
             ( (0xe4u8, 0x28u8), Field("TX_PULSE_SHAPE_CONFIG", ParamType::HexArray(3)) ),
             ( (0xe4u8, 0x30u8), Field("CLK_CONFIG_CTRL", ParamType::HexArray(2)) ),
-            ( (0xe4u8, 0x33u8), Field("NXP_EXTENDED_NTF_CONFIG", ParamType::Dec8) ),
+            ( NXP_EXTENDED_NTF_CONFIG, Field("NXP_EXTENDED_NTF_CONFIG", ParamType::Dec8) ),
             ( (0xe4u8, 0x34u8), Field("CLOCK_PRESENT_WAITING_TIME", ParamType::Dec16) ),
             ( (0xe4u8, 0x60u8), Field("ANTENNA_RX_IDX_DEFINE", ParamType::HexArray(0)) ),
             ( (0xe4u8, 0x61u8), Field("ANTENNA_TX_IDX_DEFINE", ParamType::HexArray(0)) ),
@@ -319,7 +448,7 @@ lazy_static! {
         ])
     };
 
-    static ref APP_CONF_PARAMS: HashMap<u8, Field> = {
+    pub(crate) static ref APP_CONF_PARAMS: HashMap<u8, Field> = {
         HashMap::from([
             ( 0x00u8, Field("DEVICE_TYPE", ParamType::Table8(&[(0u8, "Controlee"), (1u8, "Controller")])) ),
             ( 0x01u8, Field("RANGING_ROUND_USAGE", ParamType::Table8(&[
@@ -378,6 +507,42 @@ lazy_static! {
             ( 0x33u8, Field("BLINK_RANDOM_INTERVAL", ParamType::Dec16) ),
             ( 0x34u8, Field("TDOA_REPORT_FREQUENCY", ParamType::Dec16) ),
             ( 0x35u8, Field("STS_LENGTH", ParamType::Dec8) ),
+            /* UCI 2.0 */
+            ( 0x36u8, Field("MIN_FRAMES_PER_RR", ParamType::Dec8) ),
+            ( 0x37u8, Field("MTU_SIZE", ParamType::Dec16) ),
+            ( 0x38u8, Field("INTER_FRAME_INTERVAL", ParamType::Dec8) ),
+            ( 0x39u8, Field("DL_TDOA_RANGING_METHOD", ParamType::Table8(&[(0u8, "SS-TWR"), (1u8, "DS-TWR")])) ),
+            ( 0x3Au8, Field("DL_TDOA_TX_TIMESTAMP_CONF", ParamType::Hex8) ),
+            ( 0x3Bu8, Field("DL_TDOA_HOP_COUNT", ParamType::Hex8) ),
+            ( 0x3Cu8, Field("DL_TDOA_ANCHOR_CFO", ParamType::Hex8) ),
+            ( 0x3Du8, Field("DL_TDOA_ANCHOR_LOCATION", ParamType::HexArray(0)) ),
+            ( 0x3Eu8, Field("DL_TDOA_TX_ACTIVE_RANGING_ROUNDS", ParamType::Dec8) ),
+            ( 0x3Fu8, Field("DL_TDOA_BLOCK_SKIPPING", ParamType::Dec8) ),
+            ( 0x40u8, Field("DL_TDOA_TIME_REFERENCE_ANCHOR", ParamType::Hex8) ),
+            ( 0x41u8, Field("SESSION_KEY", ParamType::HexArray(0)) ),
+            ( 0x42u8, Field("SUBSESSION_KEY", ParamType::HexArray(0)) ),
+            ( 0x43u8, Field("SESSION_DATA_TRANSFER_STATUS_NTF_CONFIG", ParamType::Hex8) ),
+            ( 0x44u8, Field("SESSION_TIME_BASE", ParamType::HexArray(9)) ),
+            ( 0x45u8, Field("DL_TDOA_RESPONDER_TOF", ParamType::Hex8) ),
+            ( 0x46u8, Field("SECURE_RANGING_NEFA_LEVEL", ParamType::Dec8) ),
+            ( 0x47u8, Field("SECURE_RANGING_CSW_LENGTH", ParamType::Dec8) ),
+            ( 0x48u8, Field("APPLICATION_DATA_ENDPOINT", ParamType::Table8(&[(0u8, "Host"), (1u8, "Secure component")])) ),
+            ( 0x49u8, Field("OWR_AOA_MEASUREMENT_NTF_PERIOD", ParamType::Dec8) ),
+        ])
+    };
+
+    pub(crate) static ref TEST_CONF_PARAMS: HashMap<u8, Field> = {
+        HashMap::from([
+            ( 0x00u8, Field("NUM_PACKETS", ParamType::Dec32) ),
+            ( 0x01u8, Field("T_GAP", ParamType::Dec32) ),
+            ( 0x02u8, Field("T_START", ParamType::Dec32) ),
+            ( 0x03u8, Field("T_WIN", ParamType::Dec32) ),
+            ( 0x04u8, Field("RANDOMIZE_PSDU", ParamType::Table8(&[(0u8, "No randomization"), (1u8, "Randomize")])) ),
+            ( 0x05u8, Field("PHR_RANGING_BIT", ParamType::Table8(&[(0u8, "Disable"), (1u8, "Enable")])) ),
+            ( 0x06u8, Field("RMARKER_TX_START", ParamType::Dec32) ),
+            ( 0x07u8, Field("RMARKER_RX_START", ParamType::Dec32) ),
+            ( 0x08u8, Field("STS_INDEX_AUTO_INCR", ParamType::Table8(&[(0u8, "No increment"), (1u8, "Increment")])) ),
+            ( 0x09u8, Field("STS_DETECT_BITMAP", ParamType::Table8(&[(0u8, "Disable"), (1u8, "Enable")])) ),
         ])
     };
 
@@ -389,6 +554,166 @@ lazy_static! {
         ])
     };
 
+    static ref SESSION_REASON_CODES: HashMap<u8, &'static str> = { HashMap::from([
+            ( 0x00u8, "STATE_CHANGE_WITH_SESSION_MANAGEMENT_COMMANDS" ),
+            ( 0x01u8, "MAX_RANGING_ROUND_RETRY_COUNT_REACHED" ),
+            ( 0x02u8, "MAX_NUMBER_OF_MEASUREMENTS_REACHED" ),
+            ( 0x20u8, "ERROR_SLOT_LENGTH_NOT_SUPPORTED" ),
+            ( 0x21u8, "ERROR_INSUFFICIENT_SLOTS_PER_RR" ),
+            ( 0x22u8, "ERROR_MAC_ADDRESS_MODE_NOT_SUPPORTED" ),
+            ( 0x23u8, "ERROR_INVALID_RANGING_INTERVAL" ),
+            ( 0x24u8, "ERROR_INVALID_STS_CONFIG" ),
+            ( 0x25u8, "ERROR_INVALID_RFRAME_CONFIG" ),
+        ])
+    };
+
+    static ref SESSION_REASON_CODES_V2: HashMap<u8, &'static str> = {
+        let mut codes = SESSION_REASON_CODES.clone();
+        codes.extend([
+            ( 0x03u8, "SESSION_SUSPENDED_DUE_TO_INBAND_SIGNAL" ),
+            ( 0x04u8, "SESSION_RESUMED_DUE_TO_INBAND_SIGNAL" ),
+            ( 0x05u8, "SESSION_STOPPED_DUE_TO_INBAND_SIGNAL" ),
+            ( 0x1Du8, "ERROR_INVALID_UL_TDOA_RANDOM_WINDOW" ),
+            ( 0x1Eu8, "ERROR_MIN_RFRAMES_PER_RR_NOT_SUPPORTED" ),
+            ( 0x1Fu8, "ERROR_TX_DELAY_NOT_SUPPORTED" ),
+            ( 0x26u8, "ERROR_INVALID_ANTENNA_CFG" ),
+            ( 0x27u8, "ERROR_INVALID_PREAMBLE_CODE_INDEX" ),
+            ( 0x28u8, "ERROR_INVALID_SFD_ID" ),
+            ( 0x29u8, "ERROR_INVALID_PSDU_DATA_RATE" ),
+            ( 0x2Au8, "ERROR_INVALID_PHR_DATA_RATE" ),
+            ( 0x2Bu8, "ERROR_INVALID_PREAMBLE_DURATION" ),
+            ( 0x2Cu8, "ERROR_INVALID_STS_LENGTH" ),
+            ( 0x2Du8, "ERROR_INVALID_NUM_OF_STS_SEGMENTS" ),
+            ( 0x2Eu8, "ERROR_INVALID_NUM_OF_CONTROLEES" ),
+            ( 0x2Fu8, "ERROR_MAX_RANGING_REPLY_TIME_EXCEEDED" ),
+            ( 0x30u8, "ERROR_INVALID_DST_ADDRESS_LIST" ),
+            ( 0x31u8, "ERROR_INVALID_OR_NOT_FOUND_SUB_SESSION_ID" ),
+            ( 0x32u8, "ERROR_INVALID_RESULT_REPORT_CONFIG" ),
+            ( 0x33u8, "ERROR_INVALID_RANGING_ROUND_CONTROL_CONFIG" ),
+            ( 0x34u8, "ERROR_INVALID_RANGING_ROUND_USAGE" ),
+            ( 0x35u8, "ERROR_INVALID_MULTI_NODE_MODE" ),
+            ( 0x36u8, "ERROR_RDS_FETCH_FAILURE" ),
+            ( 0x37u8, "ERROR_REF_UWB_SESSION_DOES_NOT_EXIST" ),
+            ( 0x38u8, "ERROR_REF_UWB_SESSION_RANGING_DURATION_MISMATCH" ),
+            ( 0x39u8, "ERROR_REF_UWB_SESSION_INVALID_OFFSET_TIME" ),
+            ( 0x3Au8, "ERROR_REF_UWB_SESSION_LOST" ),
+        ]);
+        codes
+    };
+
+    static ref MULTICAST_STATUS_CODES: HashMap<u8, &'static str> = { HashMap::from([
+            ( 0x00u8, "OK_MULTICAST_LIST_UPDATE" ),
+            ( 0x01u8, "ERROR_MULTICAST_LIST_FULL" ),
+            ( 0x02u8, "ERROR_KEY_FETCH_FAIL" ),
+            ( 0x03u8, "ERROR_SUB_SESSION_ID_NOT_FOUND" ),
+            ( 0x04u8, "ERROR_SUB_SESSION_KEY_NOT_FOUND" ),
+            ( 0x05u8, "ERROR_SUB_SESSION_KEY_NOT_APPLICABLE" ),
+            ( 0x06u8, "ERROR_SESSION_KEY_NOT_FOUND" ),
+            ( 0x07u8, "ERROR_ADDRESS_NOT_FOUND" ),
+            ( 0x08u8, "ERROR_ADDRESS_ALREADY_PRESENT" ),
+        ])
+    };
+
+    /* CCC sessions use the FiRa parameters too */
+    static ref CCC_APP_CONF_PARAMS: HashMap<u8, Field> = {
+        let mut params: HashMap<u8, Field> = APP_CONF_PARAMS.iter().map(|(id, field)| (*id, *field)).collect();
+        params.extend([
+            ( 0xA0u8, Field("HOP_MODE_KEY", ParamType::Hex32) ),
+            ( 0xA1u8, Field("UWB_TIME0", ParamType::HexArray(8)) ),
+            ( 0xA3u8, Field("SELECTED_PROTOCOL_VERSION", ParamType::Hex16) ),
+            ( 0xA4u8, Field("SELECTED_UWB_CONFIG_ID", ParamType::Hex16) ),
+            ( 0xA5u8, Field("SELECTED_PULSESHAPE_COMBO", ParamType::Hex8) ),
+            ( 0xA6u8, Field("URSK_TTL", ParamType::Dec16) ),
+            ( 0xA8u8, Field("LAST_STS_INDEX_USED", ParamType::Hex32) ),
+        ]);
+        params
+    };
+
+    /* CORE_GET_CAPS_INFO_RSP; 0xa0.. are CCC, 0xe0.. vendor (NXP) capabilities */
+    static ref CAPS_PARAMS: HashMap<u8, Field> = {
+        HashMap::from([
+            ( 0x00u8, Field("FIRA_PHY_VERSION_RANGE", ParamType::VersionRange) ),
+            ( 0x01u8, Field("FIRA_MAC_VERSION_RANGE", ParamType::VersionRange) ),
+            ( 0x02u8, Field("DEVICE_ROLES", ParamType::Bitmask(&[
+                "Responder", "Initiator", "UT-Synchronization Anchor", "UT-Anchor",
+                "UT-Tag", "Advertiser", "Observer", "DT-Anchor", "DT-Tag",
+            ])) ),
+            ( 0x03u8, Field("RANGING_METHOD", ParamType::Bitmask(&[
+                "OWR UL-TDoA", "SS-TWR deferred", "DS-TWR deferred",
+                "SS-TWR non-deferred", "DS-TWR non-deferred", "OWR DL-TDoA",
+                "OWR for AoA", "eSS-TWR non-deferred", "aDS-TWR",
+            ])) ),
+            ( 0x04u8, Field("STS_CONFIG", ParamType::Bitmask(&[
+                "Static STS", "Dynamic STS", "Dynamic STS with sub-session key",
+                "Provisioned STS", "Provisioned STS with sub-session key",
+            ])) ),
+            ( 0x05u8, Field("MULTI_NODE_MODE", ParamType::Bitmask(&["One-to-one", "One-to-many", "Many-to-many"])) ),
+            ( 0x06u8, Field("RANGING_TIME_STRUCT", ParamType::Bitmask(&["Interval based", "Block based"])) ),
+            ( 0x07u8, Field("SCHEDULED_MODE", ParamType::Bitmask(&["Contention based", "Time scheduled", "Hybrid"])) ),
+            ( 0x08u8, Field("HOPPING_MODE", ParamType::Bitmask(&["Hopping"])) ),
+            ( 0x09u8, Field("BLOCK_STRIDING", ParamType::Bitmask(&["Block striding"])) ),
+            ( 0x0Au8, Field("UWB_INITIATION_TIME", ParamType::Bitmask(&["UWB initiation time"])) ),
+            ( 0x0Bu8, Field("CHANNELS", ParamType::Bitmask(&[
+                "Channel 5", "Channel 6", "Channel 8", "Channel 9",
+                "Channel 10", "Channel 12", "Channel 13", "Channel 14",
+            ])) ),
+            ( 0x0Cu8, Field("RFRAME_CONFIG", ParamType::Bitmask(&["SP0", "SP1", "SP2", "SP3"])) ),
+            ( 0x0Du8, Field("CC_CONSTRAINT_LENGTH", ParamType::Bitmask(&["K=3", "K=7"])) ),
+            ( 0x0Eu8, Field("BPRF_PARAMETER_SETS", ParamType::Bitmask(&["Set 1", "Set 2", "Set 3", "Set 4", "Set 5", "Set 6"])) ),
+            ( 0x0Fu8, Field("HPRF_PARAMETER_SETS", ParamType::HexArray(5)) ),
+            ( 0x10u8, Field("AOA_SUPPORT", ParamType::Bitmask(&["Azimuth 90", "Azimuth 180", "Elevation", "AoA FOM"])) ),
+            ( 0x11u8, Field("EXTENDED_MAC_ADDRESS", ParamType::Hex8) ),
+            ( 0x12u8, Field("MAX_DATA_MESSAGE_SIZE", ParamType::Dec16) ),
+            ( 0x13u8, Field("MAX_DATA_PACKET_PAYLOAD_SIZE", ParamType::Dec16) ),
+
+            ( 0xA0u8, Field("CCC_SLOT_BITMASK", ParamType::Bitmask(&[
+                "3 slots", "4 slots", "6 slots", "8 slots", "9 slots", "12 slots", "24 slots",
+            ])) ),
+            ( 0xA1u8, Field("CCC_SYNC_CODE_INDEX_BITMASK", ParamType::Bitmask(&[
+                "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12", "13", "14", "15", "16",
+                "17", "18", "19", "20", "21", "22", "23", "24", "25", "26", "27", "28", "29", "30", "31", "32",
+            ])) ),
+            ( 0xA2u8, Field("CCC_HOPPING_CONFIG_BITMASK", ParamType::Bitmask(&[
+                "Default hopping sequence", "AES hopping sequence",
+                "Adaptive hopping", "Continuous hopping", "No hopping",
+            ])) ),
+            ( 0xA3u8, Field("CCC_CHANNEL_BITMASK", ParamType::Bitmask(&["Channel 5", "Channel 9"])) ),
+            ( 0xA4u8, Field("CCC_SUPPORTED_PROTOCOL_VERSION", ParamType::HexArray(0)) ),
+            ( 0xA5u8, Field("CCC_SUPPORTED_UWB_CONFIG_ID", ParamType::HexArray(0)) ),
+            ( 0xA6u8, Field("CCC_SUPPORTED_PULSESHAPE_COMBO", ParamType::HexArray(0)) ),
+            ( 0xA7u8, Field("CCC_RAN_MULTIPLIER", ParamType::Dec8) ),
+            ( 0xA8u8, Field("CCC_MAX_RANGING_SESSION_NUMBER", ParamType::Dec8) ),
+
+            ( 0xC0u8, Field("POWER_STATS", ParamType::Hex8) ),
+            ( 0xE3u8, Field("AOA_RESULT_REQ_ANTENNA_INTERLEAVING", ParamType::Hex8) ),
+            ( 0xE4u8, Field("MIN_RANGING_INTERVAL_MS", ParamType::Dec32) ),
+            ( 0xE5u8, Field("RANGE_DATA_NTF_CONFIG", ParamType::Bitmask(&[
+                "Disable", "Enable", "Enable proximity level trigger",
+                "Enable AoA level trigger", "Enable proximity and AoA level trigger",
+                "Enable proximity edge trigger", "Enable AoA edge trigger",
+                "Enable proximity and AoA edge trigger",
+            ])) ),
+            ( 0xE6u8, Field("RSSI_REPORTING", ParamType::Hex8) ),
+            ( 0xE7u8, Field("DIAGNOSTICS", ParamType::Hex8) ),
+            ( 0xE8u8, Field("MIN_SLOT_DURATION_RSTU", ParamType::Dec32) ),
+            ( 0xE9u8, Field("MAX_RANGING_SESSION_NUMBER", ParamType::Dec8) ),
+        ])
+    };
+
+    /* vendor specific info of CORE_GET_DEVICE_INFO_RSP, NXP UWBS */
+    static ref DEVICE_INFO_NXP: HashMap<u8, Field> = HashMap::new();
+    static ref DEVICE_INFO_NXP_EXT: HashMap<(u8, u8), Field> = {
+        HashMap::from([
+            ( (0xe3u8, 0x00u8), Field("DEVICE_NAME", ParamType::CharArray(0)) ),
+            ( (0xe3u8, 0x01u8), Field("FIRMWARE_VERSION", ParamType::FwVersion) ),
+            ( (0xe3u8, 0x63u8), Field("FW_BOOT_MODE", ParamType::Table8(&[(0u8, "Factory"), (1u8, "User")])) ),
+        ])
+    };
+
+    static ref DEVCAL_PARAM_NAMES_NXP: HashMap<u8, &'static str> = {
+        DEVCAL_PARAMS_NXP.iter().map(|(id, field)| (*id, field.0)).collect()
+    };
+
     static ref DEVCAL_PARAMS_NXP: HashMap<u8, Field> = {
         HashMap::from([
             ( 0x00u8, Field("VCO_PLL", ParamType::HexArray(2)) ),
@@ -402,58 +727,52 @@ lazy_static! {
             ( 0x08u8, Field("PA_PPA_CALIB_CTRL", ParamType::HexArray(2)) ),
             ( 0x09u8, Field("TX_TEMPERATURE_COMP", ParamType::HexArray(0)) ),
             ( 0x0Au8, Field("PDOA2_OFFSET", ParamType::HexArray(0)) ),
-            ( 0x0Bu8, Field("AOA_MULTIPOINT_PDOA_CALIB", ParamType::HexArray(0)) ),
-            ( 0x0Cu8, Field("AOA_MULTIPOINT_PDOA_CALIB", ParamType::HexArray(0)) ),
+            ( 0x0Bu8, Field("AOA_MULTIPOINT_PDOA_CALIB", ParamType::Entries(&MULTIPOINT_PDOA_ENTRY)) ),
+            ( 0x0Cu8, Field("AOA_MULTIPOINT_PDOA_CALIB", ParamType::Entries(&MULTIPOINT_PDOA_ENTRY)) ),
             ( 0x0Du8, Field("AOA_ANTENNAS_MULTIPOINT_CALIB", ParamType::HexArray(0)) ),
-            ( 0x0Fu8, Field("RX_ANT_DELAY_CALIB", ParamType::HexArray(0)) ),
-            ( 0x10u8, Field("PDOA_OFFSET_CALIB", ParamType::HexArray(0)) ),
+            ( 0x0Fu8, Field("RX_ANT_DELAY_CALIB", ParamType::Entries(&[
+                Field("RX_ANTENNA_ID", ParamType::Hex8),
+                Field("DELAY", ParamType::Dec16),
+            ])) ),
+            ( 0x10u8, Field("PDOA_OFFSET_CALIB", ParamType::Entries(&[
+                Field("RX_ANTENNA_PAIR_ID", ParamType::Hex8),
+                Field("PDOA_OFFSET", ParamType::Q16(11)),
+            ])) ),
             ( 0x11u8, Field("PDOA_MANUFACT_ZERO_OFFSET_CALIB", ParamType::HexArray(0)) ),
             ( 0x12u8, Field("AOA_THRESHOLD_PDOA", ParamType::HexArray(0)) ),
-            ( 0x13u8, Field("RSSI_CALIB_CONSTANT_HIGH_PWR", ParamType::HexArray(0)) ),
-            ( 0x14u8, Field("RSSI_CALIB_CONSTANT_LOW_PWR", ParamType::HexArray(0)) ),
-            ( 0x15u8, Field("SNR_CALIB_CONSTANT_PER_ANTENNA", ParamType::HexArray(0)) ),
-            ( 0x17u8, Field("TX_POWER_PER_ANTENNA", ParamType::HexArray(0)) ),
-            ( 0x18u8, Field("TX_TEMPERATURE_COMP_PER_ANTENNA", ParamType::HexArray(0)) ),
+            ( 0x13u8, Field("RSSI_CALIB_CONSTANT_HIGH_PWR", ParamType::Entries(&RSSI_CALIB_ENTRY)) ),
+            ( 0x14u8, Field("RSSI_CALIB_CONSTANT_LOW_PWR", ParamType::Entries(&RSSI_CALIB_ENTRY)) ),
+            ( 0x15u8, Field("SNR_CALIB_CONSTANT_PER_ANTENNA", ParamType::Entries(&[
+                Field("RX_ANTENNA_ID", ParamType::Hex8),
+                Field("SNR_CALIB_CONSTANT", ParamType::Int16),
+            ])) ),
+            ( 0x17u8, Field("TX_POWER_PER_ANTENNA", ParamType::Entries(&[
+                Field("TX_ANTENNA_ID", ParamType::Hex8),
+                Field("TX_POWER_DELTA_PEAK", ParamType::Q16(8)),
+                Field("TX_POWER_ID_RMS", ParamType::Int16),
+            ])) ),
+            ( 0x18u8, Field("TX_TEMPERATURE_COMP_PER_ANTENNA", ParamType::Entries(&[
+                Field("TX_ANTENNA_ID", ParamType::Hex8),
+                Field("TEMPERATURE", ParamType::Int8),
+                Field("TX_POWER_COMPENSATION", ParamType::Q16(8)),
+            ])) ),
         ])
     };
 }
 
 fn print_hexarr(pkt: &Packet, offset: usize, len: usize) -> String {
-    (offset..offset + len).fold(String::from("{"), |arr, i| arr + format!(" {:#04x}", pkt.get(i)).as_str()) + " }"
-}
-
-trait Printer {
-    fn print_id<'a>(&self, name: &'a str);
-    fn print_comment<'a>(&self, s: &'a str);
-    fn print_param<'a>(&self, name: &'a str, val: &'a str);
+    hexarr(pkt.slice(offset, len))
 }
 
-struct BasicPrinter;
+fn print_field(field: &Field, pkt: &Packet, offset: usize, len: usize)-> Option<Value> {
 
-impl Printer for BasicPrinter {
-    fn print_id<'a>(&self, name: &'a str) {
-        println!("{}", name);
-    }
-    fn print_comment<'a>(&self, s: &'a str) {
-        println!("{}", s);
-    }
-    fn print_param<'a>(&self, name: &'a str, val: &'a str) {
-        println!("- {} = {}", name, val);
-    }
-}
-
-fn print_field(field: &Field, pkt: &Packet, offset: usize, len: usize)-> Option<String> {
-
-    macro_rules! printf {
-        ($pkt: ident, $fmt: expr, $offset: expr, $len: ident) => {
-            format!($fmt,
-                if $len == 1 {
-                    $pkt.get($offset).into()
-                } else if $len == 2 {
-                    LittleEndian::read_u16(&$pkt.slice($offset, 2)).into()
-                } else {
-                    LittleEndian::read_u32(&$pkt.slice($offset, 4))
-                })
+    fn read_uint(pkt: &Packet, offset: usize, len: usize) -> u32 {
+        if len == 1 {
+            pkt.get(offset).into()
+        } else if len == 2 {
+            LittleEndian::read_u16(pkt.slice(offset, 2)).into()
+        } else {
+            LittleEndian::read_u32(pkt.slice(offset, 4))
         }
     }
 
@@ -462,66 +781,125 @@ fn print_field(field: &Field, pkt: &Packet, offset: usize, len: usize)-> Option<
     }
 
     let ret = if !field.length_compatible(len) {
-        format!("length mismatch expected={}, actual={}", field.size(), len)
+        Value::Invalid(format!("length mismatch expected={}, actual={}", field.size(), len))
     } else {
         match field.1 {
-            ParamType::Hex8 => printf!(pkt, "{:#x}", offset, len),
-            ParamType::Hex16 => printf!(pkt, "{:#x}", offset, len),
-            ParamType::Hex32 => printf!(pkt, "{:#x}", offset, len),
-            ParamType::Dec8 => printf!(pkt, "{}", offset, len),
-            ParamType::Dec16 => printf!(pkt, "{}", offset, len),
-            ParamType::Dec32 => printf!(pkt, "{}", offset, len),
-            ParamType::CharArray(_n) => String::from_utf8_lossy(&pkt.bytes[offset..offset + len]).into_owned(),
+            ParamType::Hex8 | ParamType::Hex16 | ParamType::Hex32 => Value::Hex(read_uint(pkt, offset, len)),
+            ParamType::Dec8 | ParamType::Dec16 | ParamType::Dec32 => Value::Dec(read_uint(pkt, offset, len)),
+            ParamType::Int8 => Value::Int(pkt.get(offset) as i8 as i32),
+            ParamType::Int16 => Value::Int(LittleEndian::read_i16(pkt.slice(offset, 2)) as i32),
+            ParamType::Int32 => Value::Int(LittleEndian::read_i32(pkt.slice(offset, 4))),
+            ParamType::Q16(n) | ParamType::Q8(n) | ParamType::Angle(n) => {
+                let raw = read_uint(pkt, offset, len);
+                let v = match field.1 {
+                    ParamType::Q8(_) => raw as u8 as i8 as f64,
+                    _ => raw as u16 as i16 as f64,
+                };
+                let unit = match field.1 {
+                    ParamType::Angle(_) => Some("deg"),
+                    _ => None,
+                };
+                Value::Fixed(v / (1u32 << n) as f64, unit, raw)
+            },
+            ParamType::Version => {
+                let (major, minor) = (pkt.get(offset), pkt.get(offset + 1));
+                Value::Text(format!("{}.{}.{}", major, minor >> 4, minor & 0x0f))
+            },
+            ParamType::FwVersion => {
+                let v = pkt.slice(offset, 3);
+                Value::Text(format!("{:02X}.{:02X}.{:02X}", v[0], v[1], v[2]))
+            },
+            ParamType::VersionRange => {
+                let v = pkt.slice(offset, 4);
+                Value::Text(format!("{}.{} - {}.{}", v[0], v[1], v[2], v[3]))
+            },
+            ParamType::Bitmask(names) if len <= 4 => {
+                let bits = pkt.slice(offset, len).iter().rev().fold(0u32, |v, b| v << 8 | *b as u32);
+                let set = (0..32).filter(|i| bits & (1 << i) != 0).map(|i| match names.get(i) {
+                    Some(name) => name.to_string(),
+                    None => format!("bit {}", i),
+                });
+                Value::Flags(bits, set.collect())
+            },
+            ParamType::CharArray(_n) => Value::Text(String::from_utf8_lossy(pkt.slice(offset, len)).into_owned()),
             ParamType::Table8(t) => {
-                let mut v = None;
-                for x in t {
-                    if x.0 == pkt.get(offset) {
-                        v = Some(x);
-                        break;
-                    }
-                }
-                match v {
-                    Some(x) => format!("{:#04x} ({})", x.0, x.1),
-                    None => format!("{:#04x}(Unknown)", pkt.get(offset)),
-                }
+                let id = pkt.get(offset);
+                Value::Enum(id, t.iter().find(|x| x.0 == id).map(|x| x.1))
             },
             ParamType::Map8(t) => {
                 let id = pkt.get(offset);
-
-                match t.get(&id) {
-                    Some(x) => format!("{:#04x} ({})", id, x),
-                    None => format!("{:#04x}(Unknown)", id),
-                }
+                Value::Enum(id, t.get(&id).copied())
             }
-            _ => print_hexarr(pkt, offset, len),
+            _ => Value::Bytes(pkt.slice(offset, len).to_vec()),
         }
     };
     Some(ret)
 }
 
-fn _print_static<'a>(printer: &dyn Printer, pkt: &Packet, fields: &Vec<Field>, offset: &mut usize)-> Result<(), UciPacketParseError> {
+/* NXP calibration style list: entry count, then equally sized entries; only a last field of size 0 lets entries grow */
+fn emit_entries(printer: &dyn Printer, name: &str, fields: &[Field], pkt: &Packet, offset: usize, len: usize) {
+    let num: usize = if len > 0 { pkt.get(offset).into() } else { 0 };
+    let fixed: usize = fields.iter().map(|f| f.size()).sum();
+    let variable = fields.last().is_some_and(|f| f.size() == 0);
+    let entry = len.saturating_sub(1).checked_div(num).unwrap_or(0);
+    let fits = if variable { entry >= fixed } else { entry == fixed };
+    if len == 0 || (len - 1) != num * entry || (num > 0 && !fits) {
+        let raw = pkt.slice(offset, len);
+        printer.print_value(name, offset, raw, &Value::Invalid(format!("malformed entry list {}", hexarr(raw))));
+        return;
+    }
+
+    /* the parameter itself shows the number of entries */
+    printer.print_value(name, offset, pkt.slice(offset, 1), &Value::Dec(num as u32));
+    printer.begin_list("Entries");
+    let mut off = offset + 1;
+    for i in 0..num {
+        printer.begin_item(&format!("Entry {}", i));
+        for field in fields {
+            let size = match field.size() {
+                0 => entry - fixed,
+                n => n,
+            };
+            emit_field(printer, field.0, field, pkt, off, size);
+            off += size;
+        }
+        printer.end_item();
+    }
+    printer.end_list();
+}
+
+/* print a field through the printer's typed interface */
+fn emit_field(printer: &dyn Printer, name: &str, field: &Field, pkt: &Packet, offset: usize, len: usize) {
+    if let ParamType::Entries(fields) = field.1 {
+        emit_entries(printer, name, fields, pkt, offset, len);
+        return;
+    }
+    if let Some(v) = print_field(field, pkt, offset, len) {
+        printer.print_value(name, offset, pkt.slice(offset, len), &v);
+    }
+}
+
+fn _print_static(printer: &dyn Printer, pkt: &Packet, fields: &[Field], offset: &mut usize)-> Result<(), UciPacketParseError> {
     for field in fields {
         let len = field.size();
-        if (*offset + len) > pkt.len().into() {
+        if (*offset + len) > pkt.len() {
             return Err(UciPacketParseError::new("length mismatch"));
         }
-        if let Some(v) = print_field(&field, &pkt, *offset, len) {
-            printer.print_param(field.0, &v);
-        }
-        *offset = *offset + len;
+        emit_field(printer, field.0, field, pkt, *offset, len);
+        *offset += len;
     }
     Ok(())
 }
 
-fn print_static<'a>(printer: &dyn Printer, pkt: &Packet, fields: &Vec<Field>)-> Result<(), UciPacketParseError> {
-    _print_static(printer, &pkt, fields, &mut 0)
+fn print_static(printer: &dyn Printer, pkt: &Packet, fields: &[Field])-> Result<(), UciPacketParseError> {
+    _print_static(printer, pkt, fields, &mut 0)
 }
 
 fn print_config(printer: &dyn Printer, pkt: &Packet, off: usize,
                 table: &HashMap<u8, Field>,
                 ext_table: Option<&HashMap<(u8, u8), Field>>) -> Result<(), UciPacketParseError> {
 
-    if pkt.len() < 5 {
+    if off >= pkt.len() {
         return Err(UciPacketParseError::new("payload len is zero"));
     }
 
@@ -529,82 +907,402 @@ fn print_config(printer: &dyn Printer, pkt: &Packet, off: usize,
     let mut n = 0;
     let mut offset: usize = off + 1;
 
-    printer.print_param("Number of parameters", &format!("{}", num));
+    printer.print_value("Number of parameters", off, pkt.slice(off, 1), &Value::Dec(num.into()));
 
     while n < num {
-        if (offset + 2) > pkt.len().into() {
+        if (offset + 2) > pkt.len() {
             printer.print_param("RESIDUE", "parse error");
             break;
         }
 
         let b0 = pkt.get(offset);
         let b1 = pkt.get(offset + 1);
-        let len: usize;
-
-        if b0 < 0xe0u8 || (offset + 3) > pkt.len().into() || ext_table == None {
-            /* standard TLV */
-            len = b1.into();
-            offset = offset + 2;
-            match table.get(&b0) {
-                Some(field) => {
-                    let name = format!("{}({:#04x})", field.0, b0);
-                    let val = match print_field(field, &pkt, offset, len) {
-                        Some(v) => v,
-                        None => "BUG".to_string(),
-                    };
-                    printer.print_param(name.as_str(), val.as_str());
-                }
-                None => {
-                    printer.print_param(&format!("Unknown({:#04x} {:#04x})", b0, b1), &print_hexarr(&pkt, offset, len));
+
+        /* NXP extended TLV: id0 + id1 + len + value */
+        let ext = match ext_table {
+            Some(ext_table) if b0 >= 0xe0u8 && (offset + 3) <= pkt.len() => Some(ext_table),
+            _ => None,
+        };
+        let (len, hdr): (usize, usize) = match ext {
+            Some(_) => (pkt.get(offset + 2).into(), 3),
+            None => (b1.into(), 2),
+        };
+        offset += hdr;
+
+        if (offset + len) > pkt.len() {
+            printer.print_param("RESIDUE", "parse error");
+            break;
+        }
+
+        match ext {
+            Some(ext_table) => {
+                match ext_table.get(&(b0, b1)) {
+                    Some(field) => {
+                        let name = format!("{}({:#04x}:{:#04x})", field.0, b0, b1);
+                        emit_field(printer, &name, field, pkt, offset, len);
+                    }
+                    None => {
+                        let raw = pkt.slice(offset, len);
+                        printer.print_value(&format!("Unknown({:#04x} {:#04x})", b0, b1), offset, raw, &Value::Bytes(raw.to_vec()));
+                    }
                 }
             }
-        } else {
-            /* NXP extended TLV: id0 + id1 + len + value */
-            len = pkt.get(offset + 2).into();
-            offset = offset + 3;
-            match ext_table.unwrap().get(&(b0, b1)) {
-                Some(field) => {
-                    let name = format!("{}({:#04x}:{:#04x})", field.0, b0, b1);
-                    let val = match print_field(field, &pkt, offset, len) {
-                        Some(v) => v,
-                        None => "BUG".to_string(),
-                    };
-                    printer.print_param(name.as_str(), val.as_str());
-                }
-                None => {
-                    printer.print_param(&format!("Unknown({:#04x} {:#04x})", b0, b1), &print_hexarr(&pkt, offset, len));
+            None => {
+                /* standard TLV */
+                match table.get(&b0) {
+                    Some(field) => {
+                        let name = format!("{}({:#04x})", field.0, b0);
+                        emit_field(printer, &name, field, pkt, offset, len);
+                    }
+                    None => {
+                        let raw = pkt.slice(offset, len);
+                        printer.print_value(&format!("Unknown({:#04x} {:#04x})", b0, b1), offset, raw, &Value::Bytes(raw.to_vec()));
+                    }
                 }
             }
         };
 
-        offset = offset + len;
-        n = n + 1;
+        offset += len;
+        n += 1;
+    }
+    Ok(())
+}
+
+/* `num` entries of `fields`, one list item each */
+fn print_list(printer: &dyn Printer, pkt: &Packet, offset: &mut usize, name: &str, item: &str,
+              num: u8, fields: &[Field]) -> Result<(), UciPacketParseError> {
+    printer.begin_list(name);
+    for i in 0..num {
+        printer.begin_item(&format!("{} {}", item, i));
+        _print_static(printer, pkt, fields, offset)?;
+        printer.end_item();
+    }
+    printer.end_list();
+    Ok(())
+}
+
+/* id of a configuration parameter with its name, standard or NXP extended, and the id length */
+fn config_id(pkt: &Packet, offset: usize, table: &HashMap<u8, Field>,
+             ext_table: Option<&HashMap<(u8, u8), Field>>) -> Option<(Value, usize)> {
+    if offset >= pkt.len() {
+        return None;
+    }
+    let b0 = pkt.get(offset);
+    match ext_table {
+        Some(ext_table) if b0 >= 0xe0u8 => {
+            if offset + 2 > pkt.len() {
+                return None;
+            }
+            let b1 = pkt.get(offset + 1);
+            let name = ext_table.get(&(b0, b1)).map_or("Unknown", |f| f.0);
+            Some((Value::Text(format!("{:#04x}:{:#04x} ({})", b0, b1, name)), 2))
+        }
+        _ => Some((Value::Enum(b0, table.get(&b0).map(|f| f.0)), 1)),
+    }
+}
+
+/* number of parameters followed by their ids, as in GET_CONFIG commands */
+fn print_config_ids(printer: &dyn Printer, pkt: &Packet, off: usize,
+                    table: &HashMap<u8, Field>,
+                    ext_table: Option<&HashMap<(u8, u8), Field>>) -> Result<(), UciPacketParseError> {
+    if off >= pkt.len() {
+        return Err(UciPacketParseError::new("payload len is zero"));
+    }
+    let num = pkt.get(off);
+    printer.print_value("Number of parameters", off, pkt.slice(off, 1), &Value::Dec(num.into()));
+
+    let mut offset = off + 1;
+    for _ in 0..num {
+        let (id, len) = config_id(pkt, offset, table, ext_table).ok_or_else(|| UciPacketParseError::new("length mismatch"))?;
+        printer.print_value("PARAMETER_ID", offset, pkt.slice(offset, len), &id);
+        offset += len;
     }
     Ok(())
 }
 
+/* SET_CONFIG responses: status, then the id and status of each rejected parameter */
+fn print_config_status(printer: &dyn Printer, pkt: &Packet,
+                       table: &HashMap<u8, Field>,
+                       ext_table: Option<&HashMap<(u8, u8), Field>>) -> Result<(), UciPacketParseError> {
+    print_status_or(printer, pkt, || {
+        let num = pkt.get(1);
+        printer.print_value("Number of parameters", 1, pkt.slice(1, 1), &Value::Dec(num.into()));
+
+        let mut offset = 2;
+        printer.begin_list("Parameter status");
+        for i in 0..num {
+            let (id, len) = config_id(pkt, offset, table, ext_table).ok_or_else(|| UciPacketParseError::new("length mismatch"))?;
+            printer.begin_item(&format!("Parameter {}", i));
+            printer.print_value("PARAMETER_ID", offset, pkt.slice(offset, len), &id);
+            offset += len;
+            _print_static(printer, pkt, &[Field("STATUS", ParamType::Map8(&STATUS_CODES))], &mut offset)?;
+            printer.end_item();
+        }
+        printer.end_list();
+        Ok(())
+    })
+}
+
+/* extended TLVs in the parameter list at `off`, by id, if the list is well formed */
+fn config_ext_params(pkt: &Packet, off: usize) -> Vec<((u8, u8), &[u8])> {
+    let mut params = Vec::new();
+    if !config_fits(pkt, off, true) {
+        return params;
+    }
+    let mut offset = off + 1;
+    for _ in 0..pkt.get(off) {
+        let b0 = pkt.get(offset);
+        let hdr = if b0 >= 0xe0u8 { 3 } else { 2 };
+        let len = pkt.get(offset + hdr - 1) as usize;
+        if hdr == 3 {
+            params.push(((b0, pkt.get(offset + 1)), pkt.slice(offset + hdr, len)));
+        }
+        offset += hdr + len;
+    }
+    params
+}
+
+/* whether pkt[off..] is exactly a parameter count and that many TLVs, as print_config() expects */
+fn config_fits(pkt: &Packet, off: usize, ext: bool) -> bool {
+    if off >= pkt.len() {
+        return false;
+    }
+    let mut offset = off + 1;
+    for _ in 0..pkt.get(off) {
+        let hdr = if ext && offset < pkt.len() && pkt.get(offset) >= 0xe0u8 { 3 } else { 2 };
+        if offset + hdr > pkt.len() {
+            return false;
+        }
+        offset += hdr + pkt.get(offset + hdr - 1) as usize;
+    }
+    offset == pkt.len()
+}
+
+/* NXP_EXTENDED_NTF_CONFIG set to a non-zero value in the parameter list at `off` */
+fn nxp_ext_ntf_enabled(pkt: &Packet, off: usize) -> bool {
+    config_ext_params(pkt, off).iter().any(|(id, val)| *id == NXP_EXTENDED_NTF_CONFIG && val.iter().any(|b| *b != 0))
+}
+
 fn print_range_data_twr(printer: &dyn Printer, pkt: &Packet, offset: &mut usize,
-                        mac_type: range_data::MacType) -> Result<(), UciPacketParseError> {
+                        mac_type: range_data::MacType, version: UciVersion) -> Result<(), UciPacketParseError> {
     let arr = [
-        Field("Mac Address", match mac_type { range_data::MacType::Short => ParamType::Hex8, _ => ParamType::HexArray(8) }),
-        Field("Status", ParamType::Map8(&*STATUS_CODES)),
+        mac_address_field(mac_type),
+        Field("Status", ParamType::Map8(&STATUS_CODES)),
         Field("NLoS", ParamType::Table8(&[(0u8, "LoS"), (1u8, "NLoS")])),
         Field("Distance", ParamType::Dec16),
-        Field("AoA Azimuth", ParamType::Q16(7)),
+        Field("AoA Azimuth", ParamType::Angle(7)),
         Field("AoA Azimuth FOM", ParamType::Dec8),
-        Field("AoA Elevation", ParamType::Q16(7)),
+        Field("AoA Elevation", ParamType::Angle(7)),
         Field("AoA Elevation FOM", ParamType::Dec8),
-        Field("AoA Destination Azimuth", ParamType::Q16(7)),
+        Field("AoA Destination Azimuth", ParamType::Angle(7)),
         Field("AoA Destination Azimuth FOMR", ParamType::Dec8),
-        Field("AoA Destination Elevation", ParamType::Q16(7)),
+        Field("AoA Destination Elevation", ParamType::Angle(7)),
         Field("AoA Destination Elevation FOMR", ParamType::Dec8),
         Field("Slot Index", ParamType::Dec8),
     ];
-    _print_static(printer, &pkt, &Vec::from(arr), offset)
+    _print_static(printer, pkt, &arr, offset)?;
+
+    /* each measurement is padded to the same size for both address modes */
+    let rfu = match mac_type { range_data::MacType::Short => 12, _ => 6 };
+    match version {
+        UciVersion::V1 => _print_static(printer, pkt, &[Field("", ParamType::RFU(rfu))], offset),
+        UciVersion::V2 => _print_static(printer, pkt, &[
+                                        Field("RSSI", ParamType::Q8(1)),
+                                        Field("", ParamType::RFU(rfu - 1)),], offset),
+    }
+}
+
+fn mac_address_field(mac_type: range_data::MacType) -> Field {
+    Field("Mac Address", match mac_type { range_data::MacType::Short => ParamType::Hex16, _ => ParamType::HexArray(8) })
+}
+
+/* OWR UL-TDoA measurement; the message control selects the optional field sizes */
+fn print_range_data_tdoa(printer: &dyn Printer, pkt: &Packet, offset: &mut usize,
+                         mac_type: range_data::MacType) -> Result<(), UciPacketParseError> {
+    let mac_len = mac_address_field(mac_type).size();
+    if *offset + mac_len + 2 > pkt.len() {
+        return Err(UciPacketParseError::new("length mismatch"));
+    }
+    let control = pkt.get(*offset + mac_len + 1);
+    let rx_timestamp = if control & 0x01 == 0 { 5 } else { 8 };
+    let device_id = match (control >> 1) & 0x03 { 0 => 0, 1 => 2, 2 => 4, _ => 8 };
+    let tx_timestamp = match (control >> 3) & 0x03 { 1 => 5, 2 => 8, _ => 0 };
+
+    let mut arr = vec![
+        mac_address_field(mac_type),
+        Field("Status", ParamType::Map8(&STATUS_CODES)),
+        Field("Message Control", ParamType::Hex8),
+        Field("Frame Type", ParamType::Table8(&[(0u8, "Blink UTM"), (1u8, "Synchronization UTM")])),
+        Field("NLoS", ParamType::Table8(&[(0u8, "LoS"), (1u8, "NLoS")])),
+        Field("AoA Azimuth", ParamType::Angle(7)),
+        Field("AoA Azimuth FOM", ParamType::Dec8),
+        Field("AoA Elevation", ParamType::Angle(7)),
+        Field("AoA Elevation FOM", ParamType::Dec8),
+        Field("Frame Number", ParamType::Dec32),
+        Field("RX Timestamp", ParamType::HexArray(rx_timestamp)),
+    ];
+    if device_id > 0 {
+        arr.push(Field("UL-TDoA Device ID", ParamType::HexArray(device_id)));
+    }
+    if tx_timestamp > 0 {
+        arr.push(Field("TX Timestamp", ParamType::HexArray(tx_timestamp)));
+    }
+    _print_static(printer, pkt, &arr, offset)
+}
+
+/* OWR DL-TDoA measurement, as reported by a DT-Tag */
+fn print_range_data_dl_tdoa(printer: &dyn Printer, pkt: &Packet, offset: &mut usize,
+                            mac_type: range_data::MacType) -> Result<(), UciPacketParseError> {
+    let mac_len = mac_address_field(mac_type).size();
+    if *offset + mac_len + 4 > pkt.len() {
+        return Err(UciPacketParseError::new("length mismatch"));
+    }
+    let control = LittleEndian::read_u16(pkt.slice(*offset + mac_len + 2, 2));
+    let tx_timestamp = if control & 0x02 == 0 { 5 } else { 8 };
+    let rx_timestamp = if control & 0x08 == 0 { 5 } else { 8 };
+    let location = match (control >> 5) & 0x03 { 1 => 12, 2 => 10, _ => 0 };
+    let active_rounds = ((control >> 7) & 0x0f) as i16;
+
+    let mut arr = vec![
+        mac_address_field(mac_type),
+        Field("Status", ParamType::Map8(&STATUS_CODES)),
+        Field("Message Type", ParamType::Table8(&[(1u8, "Poll DTM"), (2u8, "Response DTM"), (3u8, "Final DTM")])),
+        Field("Message Control", ParamType::Hex16),
+        Field("Block Index", ParamType::Dec16),
+        Field("Round Index", ParamType::Dec8),
+        Field("NLoS", ParamType::Table8(&[(0u8, "LoS"), (1u8, "NLoS")])),
+        Field("AoA Azimuth", ParamType::Angle(7)),
+        Field("AoA Azimuth FOM", ParamType::Dec8),
+        Field("AoA Elevation", ParamType::Angle(7)),
+        Field("AoA Elevation FOM", ParamType::Dec8),
+        Field("RSSI", ParamType::Q8(1)),
+        Field("TX Timestamp", ParamType::HexArray(tx_timestamp)),
+        Field("RX Timestamp", ParamType::HexArray(rx_timestamp)),
+        Field("Anchor CFO", ParamType::Q16(11)),
+        Field("CFO", ParamType::Q16(11)),
+        Field("Initiator Reply Time", ParamType::Dec32),
+        Field("Responder Reply Time", ParamType::Dec32),
+        Field("Initiator-Responder ToF", ParamType::Dec16),
+    ];
+    if location > 0 {
+        arr.push(Field("DT-Anchor Location", ParamType::HexArray(location)));
+    }
+    if active_rounds > 0 {
+        arr.push(Field("Active Ranging Rounds", ParamType::HexArray(active_rounds)));
+    }
+    _print_static(printer, pkt, &arr, offset)
+}
+
+fn owr_aoa_fields(mac_type: range_data::MacType) -> [Field; 9] {
+    [
+        mac_address_field(mac_type),
+        Field("Status", ParamType::Map8(&STATUS_CODES)),
+        Field("NLoS", ParamType::Table8(&[(0u8, "LoS"), (1u8, "NLoS")])),
+        Field("Frame Sequence Number", ParamType::Dec8),
+        Field("Block Index", ParamType::Dec16),
+        Field("AoA Azimuth", ParamType::Angle(7)),
+        Field("AoA Azimuth FOM", ParamType::Dec8),
+        Field("AoA Elevation", ParamType::Angle(7)),
+        Field("AoA Elevation FOM", ParamType::Dec8),
+    ]
+}
+
+/* OWR for AoA measurement, FiRa 2.0 */
+fn print_range_data_owr_aoa(printer: &dyn Printer, pkt: &Packet, offset: &mut usize,
+                            mac_type: range_data::MacType) -> Result<(), UciPacketParseError> {
+    _print_static(printer, pkt, &owr_aoa_fields(mac_type), offset)
+}
+
+/*
+ * NXP vendor data following the measurements, filled when NXP_EXTENDED_NTF_CONFIG
+ * is enabled: RSSI per RX antenna, PDoA/AoA per antenna pair, CIR snapshot and
+ * WiFi coex status
+ */
+fn print_range_data_nxp_ext(printer: &dyn Printer, pkt: &Packet, offset: &mut usize) -> Result<(), UciPacketParseError> {
+    _print_static(printer, pkt, &[Field("Vendor specific length", ParamType::Dec8)], offset)?;
+    let vlen: usize = pkt.get(*offset - 1).into();
+    if vlen == 0 {
+        return Ok(());
+    }
+    if *offset + vlen != pkt.len() {
+        printer.print_comment("Vendor specific length does not match the payload");
+        return Ok(());
+    }
+
+    _print_static(printer, pkt, &[Field("Number of RX antennas", ParamType::Dec8)], offset)?;
+    let num = pkt.get(*offset - 1);
+    print_list(printer, pkt, offset, "RSSI per antenna", "RX antenna", num, &[
+               Field("RX antenna ID", ParamType::Hex8),
+               Field("RSSI", ParamType::Q16(8)),])?;
+
+    _print_static(printer, pkt, &[Field("Number of antenna pairs", ParamType::Dec8)], offset)?;
+    let num = pkt.get(*offset - 1);
+    print_list(printer, pkt, offset, "Antenna pairs", "Antenna pair", num, &[
+               Field("Antenna pair ID", ParamType::Hex8),
+               Field("PDoA", ParamType::Angle(7)),
+               Field("PDoA index", ParamType::Dec16),
+               Field("AoA", ParamType::Angle(7)),])?;
+
+    _print_static(printer, pkt, &[Field("CIR length", ParamType::Dec16)], offset)?;
+    let cir = LittleEndian::read_u16(pkt.slice(*offset - 2, 2));
+    print_sized(printer, pkt, offset, "CIR", cir.into())?;
+    _print_static(printer, pkt, &[
+                  Field("WiFi coex status", ParamType::Table8(&[(0u8, "Disabled"), (1u8, "No conflict"), (2u8, "Conflict")])),], offset)
+}
+
+/* `len` bytes whose length was read from the payload */
+fn print_sized(printer: &dyn Printer, pkt: &Packet, offset: &mut usize, name: &str, len: usize) -> Result<(), UciPacketParseError> {
+    let avail = pkt.len().saturating_sub(*offset);
+    if len > avail {
+        return Err(UciPacketParseError::new(&format!("{} length mismatch: expected={} actual={}", name, len, avail)));
+    }
+    let raw = pkt.slice(*offset, len);
+    printer.print_value(name, *offset, raw, &Value::Bytes(raw.to_vec()));
+    *offset += len;
+    Ok(())
+}
+
+/* whatever follows the fixed fields, if anything */
+fn print_rest(printer: &dyn Printer, pkt: &Packet, offset: usize, name: &str) {
+    if offset < pkt.len() {
+        let raw = pkt.slice(offset, pkt.len() - offset);
+        printer.print_value(name, offset, raw, &Value::Bytes(raw.to_vec()));
+    }
 }
 
-fn to_packet(s: String) -> Result<Packet, UciPacketParseError> {
-    fn parse_hexstr(s: String) -> Result<Vec<u8>, ParseIntError> {
+/* PSDU_DATA_LENGTH followed by PSDU_DATA, ending a test notification */
+fn print_psdu(printer: &dyn Printer, pkt: &Packet, offset: usize) -> Result<(), UciPacketParseError> {
+    if offset + 2 > pkt.len() {
+        return Err(UciPacketParseError::new("length mismatch"));
+    }
+    let len: usize = LittleEndian::read_u16(pkt.slice(offset, 2)).into();
+    printer.print_value("PSDU_DATA_LENGTH", offset, pkt.slice(offset, 2), &Value::Dec(len as u32));
+    if offset + 2 + len > pkt.len() {
+        return Err(UciPacketParseError::new(&format!("psdu length mismatch: expected={} actual={}", len, pkt.len() - offset - 2)));
+    }
+    let raw = pkt.slice(offset + 2, len);
+    printer.print_value("PSDU_DATA", offset + 2, raw, &Value::Bytes(raw.to_vec()));
+    print_rest(printer, pkt, offset + 2 + len, "VENDOR_SPECIFIC_DATA");
+    Ok(())
+}
+
+/* APPLICATION_DATA_LENGTH followed by APPLICATION_DATA, ending a data packet */
+fn print_app_data(printer: &dyn Printer, pkt: &Packet, offset: usize) -> Result<(), UciPacketParseError> {
+    if offset + 2 > pkt.len() {
+        return Err(UciPacketParseError::new("length mismatch"));
+    }
+    let len: usize = LittleEndian::read_u16(pkt.slice(offset, 2)).into();
+    printer.print_value("APPLICATION_DATA_LENGTH", offset, pkt.slice(offset, 2), &Value::Dec(len as u32));
+    if offset + 2 + len != pkt.len() {
+        return Err(UciPacketParseError::new(&format!("application data length mismatch: expected={} actual={}", len, pkt.len() - offset - 2)));
+    }
+    let raw = pkt.slice(offset + 2, len);
+    printer.print_value("APPLICATION_DATA", offset + 2, raw, &Value::Bytes(raw.to_vec()));
+    Ok(())
+}
+
+fn to_packet(s: &str) -> Result<Packet, UciPacketParseError> {
+    fn parse_hexstr(s: &str) -> Result<Vec<u8>, ParseIntError> {
         let n = if s.len() % 2 == 1 { s.len() - 1 } else { s.len() };
         (0..n)
             .step_by(2)
@@ -612,209 +1310,2227 @@ fn to_packet(s: String) -> Result<Packet, UciPacketParseError> {
             .collect()
     }
 
-    let bytes = match parse_hexstr(s) {
-        Ok(bytes) => bytes,
-        Err(_e) => return Err(UciPacketParseError::new("Failed to parse hex string"))
-    };
+    match parse_hexstr(s) {
+        Ok(bytes) => Packet::from_bytes(&bytes),
+        Err(_e) => Err(UciPacketParseError::new("Failed to parse hex string"))
+    }
+}
+
+/*
+ * Segmentation: a message larger than one packet is split into segments with
+ * PBF=1, the last one carrying PBF=0. Segments are buffered per (MT, GID, OID)
+ * and handed out as a single packet once the final segment arrives.
+ */
+#[derive(Default)]
+pub struct Reassembler {
+    pending: HashMap<PacketId, Packet>,
+}
+
+impl Reassembler {
+    pub fn new() -> Reassembler {
+        Reassembler {
+            pending: HashMap::new(),
+        }
+    }
 
-    if bytes.len() < 4 {
-        return Err(UciPacketParseError::new("packet length is less than 4 bytes"))
+    /* drop unfinished messages sent in the same direction as `id` but for another message */
+    fn take_interleaved(&mut self, id: &PacketId) -> Vec<UciPacketParseError> {
+        let stale: Vec<PacketId> = self.pending.keys()
+            .filter(|k| *k != id && Direction::of_id(k) == Direction::of_id(id))
+            .copied()
+            .collect();
+        stale.iter().map(|k| {
+            let pkt = self.pending.remove(k).unwrap();
+            UciPacketParseError::new(&format!("interleaved segment: incomplete message {} dropped", pkt))
+        }).collect()
     }
 
-    let packet_len = bytes[3];
-    if (bytes.len() - 4) != packet_len.into() {
-        return Err(UciPacketParseError::new(format!("payload length mismatch: packet_len={} actual={}", packet_len, bytes.len() - 4).as_str()));
+    /* returns errors for dropped segments followed by the packet, if complete */
+    pub fn push(&mut self, pkt: Packet) -> Vec<Result<Packet, UciPacketParseError>> {
+        let id = PacketId::from(&pkt);
+        let mut ret: Vec<Result<Packet, UciPacketParseError>> = self.take_interleaved(&id).into_iter().map(Err).collect();
+
+        let pkt = match self.pending.remove(&id) {
+            Some(mut head) => {
+                head.bytes.extend_from_slice(pkt.payload());
+                head.bytes[0] = pkt.bytes[0];
+                head
+            }
+            None => pkt,
+        };
+
+        if pkt.pbf() {
+            self.pending.insert(id, pkt);
+        } else {
+            /* the header of the first segment only covered that segment */
+            ret.push(Ok(Packet::build(&id, false, pkt.payload())));
+        }
+        ret
     }
 
-    Ok(Packet::new(bytes))
+    /* report segments still waiting for their final segment */
+    pub fn flush(&mut self) -> Vec<UciPacketParseError> {
+        self.pending.drain().map(|(_, pkt)| {
+            UciPacketParseError::new(&format!("orphaned segment: incomplete message {} dropped", pkt))
+        }).collect()
+    }
 }
 
-struct PacketDesc {
-    name: &'static str,
-    print: fn(printer: &dyn Printer, pkt: &Packet) -> Result<(), UciPacketParseError>,
+/* UCI generic specification version a trace follows */
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum UciVersion {
+    #[default]
+    V1,
+    V2,
 }
 
-lazy_static! {
-    static ref PACKETS: HashMap<PacketId, PacketDesc> = {
-        macro_rules! define_printer {
-            ($gid: ident, $oid: ident, $mt: ident, $printer: expr) => {
-                (
-                    PacketId::from((gid::$gid, oid::$oid, mt::$mt)),
-                    PacketDesc {
-                        name: concat!(stringify!($oid), "_", stringify!($mt)),
-                        print: $printer,
-                    },
-                )
-            }
-        }
+/* State of the stream seen so far; empty when a packet is decoded on its own */
+#[derive(Default)]
+pub(crate) struct Context {
+    version: UciVersion,
+    /* set by the user; CORE_GET_DEVICE_INFO_RSP does not override it */
+    version_fixed: bool,
+    /* last command and whether its response was seen */
+    last_cmd: Option<(PacketId, bool)>,
+    /* session ID of the last SESSION_INIT_CMD */
+    session_init: Option<u32>,
+    /* UCI 2.0 session handle -> session ID */
+    handles: HashMap<u32, u32>,
+    /* session ID -> SESSION_TYPE of its SESSION_INIT_CMD */
+    session_types: HashMap<u32, u8>,
+    /* session ID or handle of the last SESSION_SET/GET_APP_CONFIG_CMD */
+    app_config_session: Option<u32>,
+    /* parameter of the last NXP_GET_CALIBRATION_CMD or NXP_READ_CALIB_DATA_CMD */
+    calibration: Option<u8>,
+    /* NXP UWBS, from its vendor info or a non-zero NXP_EXTENDED_NTF_CONFIG */
+    nxp: bool,
+}
 
-        fn print_status_only(printer: &dyn Printer, pkt: &Packet) -> Result<(), UciPacketParseError> {
-            print_static(printer, pkt, &vec![Field("STATUS", ParamType::Map8(&*STATUS_CODES))])
+impl Context {
+    /* fixed to `version`, whatever CORE_GET_DEVICE_INFO_RSP says */
+    fn with_version(version: UciVersion) -> Context {
+        Context {
+            version,
+            version_fixed: true,
+            ..Default::default()
         }
+    }
 
-        HashMap::from([
-            define_printer!(CORE, CORE_DEVICE_RESET, RSP, print_status_only),
-            define_printer!(CORE, CORE_DEVICE_STATUS, NTF,
-                |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
-                    print_static(printer, pkt, &vec![Field("STATUS", ParamType::Map8(&*DEVICE_STATUS_CODES))])
-                }
-            ),
-            define_printer!(CORE, CORE_SET_CONFIG, CMD,
-                |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
-                    print_config(printer, pkt, 0, &*DEVICE_CONF_PARAMS, Some(&*DEVICE_CONF_PARAMS_NXP))
+    /* called after each complete packet is printed */
+    fn update(&mut self, pkt: &Packet) {
+        let id = PacketId::from(pkt);
+        match id.2 {
+            mt::CMD => {
+                self.last_cmd = Some((id, false));
+                if id == (gid::SESSION, oid::SESSION_INIT, mt::CMD) && pkt.len() >= 4 {
+                    let session_id = LittleEndian::read_u32(pkt.slice(0, 4));
+                    self.session_init = Some(session_id);
+                    if pkt.len() >= 5 {
+                        self.session_types.insert(session_id, pkt.get(4));
+                    }
                 }
-            ),
-            define_printer!(CORE, CORE_SET_CONFIG, RSP, print_status_only),
-
-            define_printer!(SESSION, SESSION_INIT, CMD,
-                |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
-                    print_static(printer, pkt, &vec![Field("SESSION_ID", ParamType::Hex32), Field("SESSION_TYPE", ParamType::Hex8)])
+                if (id == (gid::SESSION, oid::SESSION_SET_APP_CONFIG, mt::CMD)
+                    || id == (gid::SESSION, oid::SESSION_GET_APP_CONFIG, mt::CMD)) && pkt.len() >= 4 {
+                    self.app_config_session = Some(LittleEndian::read_u32(pkt.slice(0, 4)));
                 }
-            ),
-            define_printer!(SESSION, SESSION_INIT, RSP, print_status_only),
-            define_printer!(SESSION, SESSION_STATUS, NTF,
-                |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
-                    print_static(printer, pkt, &vec![
-                                 Field("SESSION_ID", ParamType::Hex32),
-                                 Field("SESSION_STATE", ParamType::Map8(&*SESSION_STATE_CODES)),
-                                 Field("REASON_CODE", ParamType::Hex8),])
-                    }
-            ),
-            define_printer!(SESSION, SESSION_SET_APP_CONFIG, CMD,
-                |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
-                    print_static(printer, pkt, &vec![Field("SESSION_ID", ParamType::Hex32)])?;
-                    print_config(printer, pkt, 4, &*APP_CONF_PARAMS, None)?;
-                    Ok(())
+                if (id == (gid::PROPRIETARY, oid::NXP_GET_CALIBRATION, mt::CMD)
+                    || id == (gid::PROPRIETARY_A, oid::NXP_READ_CALIB_DATA, mt::CMD)) && pkt.len() >= 2 {
+                    self.calibration = Some(pkt.get(1));
                 }
-            ),
-            define_printer!(SESSION, SESSION_SET_APP_CONFIG, RSP, print_status_only),
-
-            define_printer!(PROPRIETARY, NXP_CORE_DEVICE_INIT, CMD,
-                |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
-                    print_static(printer, pkt, &vec![Field("MAJOR_VER", ParamType::Hex8), Field("MINOR_VER", ParamType::Hex8)])
+                if id == (gid::CORE, oid::CORE_SET_CONFIG, mt::CMD) {
+                    self.nxp |= nxp_ext_ntf_enabled(pkt, 0);
                 }
-            ),
-            define_printer!(PROPRIETARY, NXP_CORE_DEVICE_INIT, RSP, print_status_only),
-
-            define_printer!(PROPRIETARY, NXP_SET_CALIBRATION, CMD,
-                |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
-                    if pkt.len() < 3 {
-                        return Err(UciPacketParseError::new("payload len mismatch"));
-                    }
-                    printer.print_param("Channel", &format!("{}", pkt.get(0)));
-                    let id = pkt.get(1);
-                    match DEVCAL_PARAMS_NXP.get(&id) {
-                        Some(field) => {
-                            let name = format!("{}({:#04x})", field.0, id);
-                            let val = match print_field(field, &pkt, 2, (pkt.len() - 2).into()) {
-                                Some(v) => v,
-                                None => "BUG".to_string(),
-                            };
-                            printer.print_param(name.as_str(), val.as_str());
-                        }
-                        None => {
-                            printer.print_param(&format!("{:#4x}:Unknown", id), &print_hexarr(&pkt, 2, (pkt.len() - 2).into()));
-                        }
-                    }
-                    Ok(())
+            }
+            mt::RSP => {
+                if let Some((cmd, answered)) = &mut self.last_cmd {
+                    *answered |= (cmd.0, cmd.1) == (id.0, id.1);
                 }
-            ),
-            define_printer!(PROPRIETARY, NXP_SET_CALIBRATION, RSP, print_status_only),
-
-            define_printer!(PROPRIETARY, NXP_SE_COMM_ERROR, NTF,
-                |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
-                    print_static(printer, pkt, &vec![Field("STATUS", ParamType::Map8(&*STATUS_CODES)),
-                        Field("CLA_INS", ParamType::Hex16),
-                        Field("T=1_STATUS_CODE", ParamType::Hex16)])
+                if id == (gid::CORE, oid::CORE_GET_DEVICE_INFO, mt::RSP) && pkt.len() >= 3 && pkt.get(0) == 0 && !self.version_fixed {
+                    self.version = if pkt.get(1) >= 2 { UciVersion::V2 } else { UciVersion::V1 };
                 }
-            ),
-            define_printer!(PROPRIETARY, NXP_BINDING_STAT, NTF,
-                |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
-                    print_static(printer, pkt, &vec![
-                                 Field("STATUS", ParamType::Table8(&[(0u8, "Not bound"), (1u8, "Bound,unlocked"), (2u8, "Bound,locked"), (3u8, "Unknown")])),
-                                 Field("SE binding count", ParamType::Dec8),
-                                 Field("UWBS binding count", ParamType::Dec8)])
+                /* vendor info follows the status, four versions and its length */
+                if id == (gid::CORE, oid::CORE_GET_DEVICE_INFO, mt::RSP) && pkt.len() > 10 && pkt.get(0) == 0 {
+                    self.nxp |= config_ext_params(pkt, 10).iter().any(|(id, _)| DEVICE_INFO_NXP_EXT.contains_key(id));
                 }
-            ),
-
-            define_printer!(RANGING, RANGE_START, RSP, print_status_only),
-            define_printer!(RANGING, RANGE_START, CMD,
-                |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
-                    print_static(printer, pkt, &vec![Field("SESSION_ID", ParamType::Hex32)])
+                if id == (gid::CORE, oid::CORE_GET_CONFIG, mt::RSP) && pkt.len() > 1 && pkt.get(0) == 0 {
+                    self.nxp |= nxp_ext_ntf_enabled(pkt, 1);
                 }
-            ),
-            define_printer!(RANGING, RANGE_DATA, NTF,
-                |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
-                    let len = pkt.len();
-
-                    if len < 25 {
-                        return Err(UciPacketParseError::new(&format!("mismatch length")));
-                    }
-
-                    let nr: u8 = pkt.get(24);
-                    let mut offset: usize = 24;
-                    let mac_type: range_data::MacType = pkt.get(16).into();
-                    let report_type: range_data::ReportType = pkt.get(13).into();
-
-                    let arr = [
-                        Field("Sequence number", ParamType::Dec32),
-                        Field("Session ID", ParamType::Hex32),
-                        Field("", ParamType::RFU(1)),
-                        Field("Ranging interval", ParamType::Dec32),
-                        Field("Ranging type", ParamType::Table8(&[(0u8, "TDoA"), (1u8, "TWR"), (2u8, "Down TDoA")])),
-                        Field("", ParamType::RFU(1)),
-                        Field("Mac addressing mode", ParamType::Table8(&[(0u8, "short"), (1u8, "long")])),
-                        Field("", ParamType::RFU(8)),
-                        Field("Number of Ranging Measurements", ParamType::Dec8),
-                    ];
-                    if let Err(e) = print_static(printer, pkt, &Vec::from(arr)) {
-                        return Err(e);
+                /* UCI 2.0: status + session handle */
+                if id == (gid::SESSION, oid::SESSION_INIT, mt::RSP) && self.version == UciVersion::V2 && pkt.len() >= 5 && pkt.get(0) == 0 {
+                    if let Some(session_id) = self.session_init.take() {
+                        self.handles.insert(LittleEndian::read_u32(pkt.slice(1, 4)), session_id);
                     }
-
-                    println!("nr = {}", nr);
-                    for i in 0..nr {
-                        printer.print_comment(&format!("Report {}", i));
-                        match report_type {
-                            range_data::ReportType::Twr => {
-                                if let Err(e) = print_range_data_twr(printer, pkt, &mut offset, mac_type) {
-                                    return Err(e);
-                                }
-                            }
-                            _ => {
-                                return Err(UciPacketParseError::new(&format!("unsupported measurement type {}", report_type as u8)));
-                            }
-                        }
-                    }
-                    Ok(())
                 }
-            ),
-            ])
-    };
-}
-
-fn print_packet(pkt: Packet) -> Result<(), UciPacketParseError> {
-    let id = PacketId::from((pkt.gid(), pkt.oid(), pkt.mt()));
-    match PACKETS.get(&id) {
-        Some(desc) => {
-            let printer = BasicPrinter;
-            printer.print_id(desc.name);
-            (desc.print)(&printer, &pkt)
+            }
+            _ => (),
         }
-        None => Err(UciPacketParseError::new(&format!("unrecognized packet {} => payload: {}", pkt, &print_hexarr(&pkt, 0, pkt.len().into())))),
     }
-}
 
-pub fn parse(s: String) {
-    match to_packet(s) {
-        Ok(pkt) => {
-            match print_packet(pkt) {
-                Ok(_) => (),
-                Err(e) => println!("{}", e),
-            };
-        }
-        Err(e) => {
-            println!("{}", e);
+    /* `session` is a session ID (UCI 1.x) or handle (2.0) */
+    fn is_ccc(&self, session: u32) -> bool {
+        let session_id = match self.version {
+            UciVersion::V1 => session,
+            UciVersion::V2 => *self.handles.get(&session).unwrap_or(&session),
+        };
+        self.session_types.get(&session_id) == Some(&SESSION_TYPE_CCC)
+    }
+
+    fn app_conf_params(&self, session: Option<u32>) -> &'static HashMap<u8, Field> {
+        match session {
+            Some(session) if self.is_ccc(session) => &CCC_APP_CONF_PARAMS,
+            _ => &APP_CONF_PARAMS,
         }
     }
 }
 
+/* session ID or handle at the start of the payload */
+fn leading_session(pkt: &Packet) -> Option<u32> {
+    if pkt.len() >= 4 {
+        Some(LittleEndian::read_u32(pkt.slice(0, 4)))
+    } else {
+        None
+    }
+}
+
+/* UCI 2.0 messages carry the handle from SESSION_INIT_RSP instead of the session ID */
+fn session_fields(ctx: &Context) -> &'static [Field] {
+    match ctx.version {
+        UciVersion::V1 => &SESSION_ID_FIELDS,
+        UciVersion::V2 => &SESSION_HANDLE_FIELDS,
+    }
+}
+
+/* the session ID behind the handle at `offset`, once SESSION_INIT_RSP assigned it */
+fn print_session_of_handle(printer: &dyn Printer, pkt: &Packet, offset: usize, ctx: &Context) {
+    if offset + 4 > pkt.len() {
+        return;
+    }
+    if let Some(id) = ctx.handles.get(&LittleEndian::read_u32(pkt.slice(offset, 4))) {
+        printer.print_param("ORIGINAL_SESSION_ID", &format!("{:#x}", id));
+    }
+}
+
+/* leading session ID (UCI 1.x) or handle (2.0) */
+fn print_session(printer: &dyn Printer, pkt: &Packet, ctx: &Context) -> Result<(), UciPacketParseError> {
+    print_static(printer, pkt, session_fields(ctx))?;
+    print_session_of_handle(printer, pkt, 0, ctx);
+    Ok(())
+}
+
+type Decoder = fn(printer: &dyn Printer, pkt: &Packet, ctx: &Context) -> Result<(), UciPacketParseError>;
+
+struct PacketDesc {
+    name: &'static str,
+    print: Decoder,
+    wrap: fn(Message) -> Result<UciMessage, UciPacketParseError>,
+}
+
+/* decoders marked `ctx` also receive the stream context */
+macro_rules! define_printer {
+    ($variant: ident, (DATA, $dpf: ident, ctx $printer: expr)) => {
+        define_printer!(@desc $variant, PacketId::from((dpf::$dpf, 0, mt::DATA)), stringify!($dpf), $printer)
+    };
+    ($variant: ident, (DATA, $dpf: ident, $printer: expr)) => {
+        define_printer!($variant, (DATA, $dpf, ctx |printer: &dyn Printer, pkt: &Packet, _: &Context| ($printer)(printer, pkt)))
+    };
+    ($variant: ident, ($gid: ident, $oid: ident, $mt: ident, ctx $printer: expr)) => {
+        define_printer!(@desc $variant, PacketId::from((gid::$gid, oid::$oid, mt::$mt)),
+                        concat!(stringify!($oid), "_", stringify!($mt)), $printer)
+    };
+    ($variant: ident, ($gid: ident, $oid: ident, $mt: ident, $printer: expr)) => {
+        define_printer!($variant, ($gid, $oid, $mt, ctx |printer: &dyn Printer, pkt: &Packet, _: &Context| ($printer)(printer, pkt)))
+    };
+    (@desc $variant: ident, $id: expr, $name: expr, $printer: expr) => {
+        (
+            $id,
+            PacketDesc {
+                name: $name,
+                print: $printer,
+                wrap: |msg| FromMessage::from_message(msg).map(UciMessage::$variant),
+            },
+        )
+    };
+}
+
+/* payload of a UciMessage variant: the type given with the variant, else the generic Message */
+macro_rules! payload {
+    () => { Message };
+    ($payload: ty) => { $payload };
+}
+
+/* generates PACKETS and UciMessage, with one variant per packet */
+macro_rules! define_packets {
+    ($($variant: ident $(($payload: ty))?: $args: tt,)*) => {
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum UciMessage {
+            $($variant(payload!($($payload)?)),)*
+        }
+
+        lazy_static! {
+            static ref PACKETS: HashMap<PacketId, PacketDesc> = {
+                HashMap::from([
+                    $(define_printer!($variant, $args),)*
+                ])
+            };
+        }
+    }
+}
+
+/* payload layouts shared with the encoder */
+pub(crate) static SESSION_INIT_CMD_FIELDS: [Field; 2] = [
+    Field("SESSION_ID", ParamType::Hex32),
+    Field("SESSION_TYPE", ParamType::Table8(&[
+        (0x00u8, "Ranging session"), (0x01u8, "Ranging and in-band data"), (0x02u8, "Data transfer"),
+        (0x03u8, "Ranging only phase"), (0x04u8, "In-band data phase"), (0x05u8, "Ranging with data phase"),
+        (SESSION_TYPE_CCC, "CCC"), (0xD0u8, "Device test mode"),
+    ])),
+];
+const SESSION_TYPE_CCC: u8 = 0xA0;
+pub(crate) static SESSION_ID_FIELDS: [Field; 1] = [Field("SESSION_ID", ParamType::Hex32)];
+static SESSION_HANDLE_FIELDS: [Field; 1] = [Field("SESSION_HANDLE", ParamType::Hex32)];
+pub(crate) static NXP_CORE_DEVICE_INIT_CMD_FIELDS: [Field; 2] = [Field("MAJOR_VER", ParamType::Hex8), Field("MINOR_VER", ParamType::Hex8)];
+pub(crate) static NXP_TEST_LOOP_CMD_FIELDS: [Field; 2] = [
+    Field("LOOP_COUNT", ParamType::Dec16),
+    Field("INTERVAL_MS", ParamType::Dec16),
+];
+static BINDING_STATES: [(u8, &str); 4] = [(0u8, "Not bound"), (1u8, "Bound,unlocked"), (2u8, "Bound,locked"), (3u8, "Unknown")];
+pub(crate) static NXP_DBG_LOG_CONFIG_CMD_FIELDS: [Field; 2] = [
+    Field("LOG_LEVEL", ParamType::Table8(&[(0u8, "Off"), (1u8, "Error"), (2u8, "Warning"), (3u8, "Info"), (4u8, "Debug")])),
+    Field("LOG_MASK", ParamType::Hex32),
+];
+pub(crate) static NXP_CIR_CAPTURE_CMD_FIELDS: [Field; 3] = [
+    Field("SESSION_ID", ParamType::Hex32),
+    Field("RX_ANTENNA_ID", ParamType::Hex8),
+    Field("NUM_TAPS", ParamType::Dec16),
+];
+/* binding state with the remaining SE and UWBS binding counts */
+static BINDING_STATUS_FIELDS: [Field; 3] = [
+    Field("STATUS", ParamType::Table8(&BINDING_STATES)),
+    Field("SE binding count", ParamType::Dec8),
+    Field("UWBS binding count", ParamType::Dec8),
+];
+pub(crate) static DATA_MESSAGE_SND_FIELDS: [Field; 3] = [
+    Field("SESSION_HANDLE", ParamType::Hex32),
+    Field("DESTINATION_ADDRESS", ParamType::HexArray(8)),
+    Field("SEQUENCE_NUMBER", ParamType::Dec16),
+];
+
+/* id of the message printed as `name`, e.g. "SESSION_INIT_CMD" */
+pub(crate) fn find_packet(name: &str) -> Option<PacketId> {
+    PACKETS.iter().find(|(_, desc)| desc.name == name).map(|(id, _)| *id)
+}
+
+fn print_empty(_printer: &dyn Printer, pkt: &Packet) -> Result<(), UciPacketParseError> {
+    if !pkt.is_empty() {
+        return Err(UciPacketParseError::new(&format!("unexpected payload {}", print_hexarr(pkt, 0, pkt.len()))));
+    }
+    Ok(())
+}
+
+/* test commands carrying the PSDU to send or expect */
+fn print_psdu_cmd(printer: &dyn Printer, pkt: &Packet) -> Result<(), UciPacketParseError> {
+    let raw = pkt.payload();
+    printer.print_value("PSDU_DATA", 0, raw, &Value::Bytes(raw.to_vec()));
+    Ok(())
+}
+
+/* channel, calibration parameter and its value */
+fn print_calibration_write(printer: &dyn Printer, pkt: &Packet) -> Result<(), UciPacketParseError> {
+    if pkt.len() < 3 {
+        return Err(UciPacketParseError::new("payload len mismatch"));
+    }
+    printer.print_value("Channel", 0, pkt.slice(0, 1), &Value::Dec(pkt.get(0).into()));
+    print_calibration(printer, pkt, pkt.get(1), 2);
+    Ok(())
+}
+
+fn print_calibration_read(printer: &dyn Printer, pkt: &Packet) -> Result<(), UciPacketParseError> {
+    let mut offset = 0;
+    _print_static(printer, pkt, &[
+                  Field("Channel", ParamType::Dec8),
+                  Field("Parameter", ParamType::Map8(&DEVCAL_PARAM_NAMES_NXP)),], &mut offset)?;
+    print_rest(printer, pkt, offset, "Unknown");
+    Ok(())
+}
+
+/* status, then the value of the parameter requested last */
+fn print_calibration_value(printer: &dyn Printer, pkt: &Packet, ctx: &Context) -> Result<(), UciPacketParseError> {
+    print_status_or(printer, pkt, || {
+        match ctx.calibration {
+            Some(id) => print_calibration(printer, pkt, id, 1),
+            None => print_rest(printer, pkt, 1, "Calibration value"),
+        }
+        Ok(())
+    })
+}
+
+/* value of NXP calibration parameter `id`, from `offset` to the end */
+fn print_calibration(printer: &dyn Printer, pkt: &Packet, id: u8, offset: usize) {
+    let len = pkt.len() - offset;
+    match DEVCAL_PARAMS_NXP.get(&id) {
+        Some(field) => {
+            let name = format!("{}({:#04x})", field.0, id);
+            emit_field(printer, &name, field, pkt, offset, len);
+        }
+        None => {
+            let raw = pkt.slice(offset, len);
+            printer.print_value(&format!("{:#4x}:Unknown", id), offset, raw, &Value::Bytes(raw.to_vec()));
+        }
+    }
+}
+
+fn print_status_only(printer: &dyn Printer, pkt: &Packet) -> Result<(), UciPacketParseError> {
+    print_static(printer, pkt, &[Field("STATUS", ParamType::Map8(&STATUS_CODES))])
+}
+
+/* status, then what `rest` prints; a failed request may carry the status alone */
+fn print_status_or(printer: &dyn Printer, pkt: &Packet,
+                   rest: impl FnOnce() -> Result<(), UciPacketParseError>) -> Result<(), UciPacketParseError> {
+    print_status_only(printer, pkt)?;
+    if pkt.len() == 1 {
+        return Ok(());
+    }
+    rest()
+}
+
+define_packets! {
+    CoreDeviceResetRsp(StatusOnly): (CORE, CORE_DEVICE_RESET, RSP, print_status_only),
+    CoreDeviceStatusNtf(CoreDeviceStatusNtf): (CORE, CORE_DEVICE_STATUS, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &[Field("STATUS", ParamType::Map8(&DEVICE_STATUS_CODES))])
+        }
+    ),
+    CoreGetDeviceInfoCmd(Empty): (CORE, CORE_GET_DEVICE_INFO, CMD, print_empty),
+    CoreGetDeviceInfoRsp(CoreGetDeviceInfoRsp): (CORE, CORE_GET_DEVICE_INFO, RSP,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            let mut offset = 0;
+            _print_static(printer, pkt, &[
+                          Field("STATUS", ParamType::Map8(&STATUS_CODES)),
+                          Field("UCI_GENERIC_VERSION", ParamType::Version),
+                          Field("MAC_VERSION", ParamType::Version),
+                          Field("PHY_VERSION", ParamType::Version),
+                          Field("UCI_TEST_VERSION", ParamType::Version),
+                          Field("VENDOR_SPECIFIC_INFO_LENGTH", ParamType::Dec8),], &mut offset)?;
+            let len: usize = pkt.get(offset - 1).into();
+            if offset + len != pkt.len() {
+                return Err(UciPacketParseError::new(&format!("vendor info length mismatch: expected={} actual={}", len, pkt.len() - offset)));
+            }
+            let raw = pkt.slice(offset, len);
+            printer.print_value("VENDOR_SPECIFIC_INFO", offset, raw, &Value::Bytes(raw.to_vec()));
+
+            /* NXP UWBS report their firmware as extended TLVs */
+            if len > 0 && config_fits(pkt, offset, true) {
+                printer.print_comment("NXP vendor info");
+                print_config(printer, pkt, offset, &DEVICE_INFO_NXP, Some(&DEVICE_INFO_NXP_EXT))?;
+            }
+            Ok(())
+        }
+    ),
+    CoreGetCapsInfoCmd(Empty): (CORE, CORE_GET_CAPS_INFO, CMD, print_empty),
+    CoreGetCapsInfoRsp(ConfigRsp): (CORE, CORE_GET_CAPS_INFO, RSP,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_status_or(printer, pkt, || print_config(printer, pkt, 1, &CAPS_PARAMS, None))
+        }
+    ),
+    CoreGenericErrorNtf(StatusOnly): (CORE, CORE_GENERIC_ERROR, NTF, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            print_status_only(printer, pkt)?;
+            match ctx.last_cmd.and_then(|(id, answered)| PACKETS.get(&id).map(|desc| (desc.name, answered))) {
+                Some((name, false)) => printer.print_comment(&format!("Likely refers to {} (not answered yet)", name)),
+                Some((name, true)) => printer.print_comment(&format!("Last command: {}", name)),
+                None => (),
+            }
+            Ok(())
+        }
+    ),
+    CoreSetConfigCmd(Config): (CORE, CORE_SET_CONFIG, CMD,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_config(printer, pkt, 0, &DEVICE_CONF_PARAMS, Some(&DEVICE_CONF_PARAMS_NXP))
+        }
+    ),
+    CoreSetConfigRsp(SetConfigRsp): (CORE, CORE_SET_CONFIG, RSP,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_config_status(printer, pkt, &DEVICE_CONF_PARAMS, Some(&DEVICE_CONF_PARAMS_NXP))
+        }
+    ),
+    CoreGetConfigCmd(GetConfigCmd): (CORE, CORE_GET_CONFIG, CMD,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_config_ids(printer, pkt, 0, &DEVICE_CONF_PARAMS, Some(&DEVICE_CONF_PARAMS_NXP))
+        }
+    ),
+    CoreGetConfigRsp(ConfigRsp): (CORE, CORE_GET_CONFIG, RSP,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_status_or(printer, pkt, || print_config(printer, pkt, 1, &DEVICE_CONF_PARAMS, Some(&DEVICE_CONF_PARAMS_NXP)))
+        }
+    ),
+
+    SessionInitCmd(SessionInitCmd): (SESSION, SESSION_INIT, CMD,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &SESSION_INIT_CMD_FIELDS)
+        }
+    ),
+    SessionInitRsp(SessionInitRsp): (SESSION, SESSION_INIT, RSP, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            print_status_only(printer, pkt)?;
+            /* UCI 2.0 assigns a handle used in place of the session ID from now on */
+            if pkt.len() > 1 && ctx.version == UciVersion::V2 {
+                _print_static(printer, pkt, &[Field("SESSION_HANDLE", ParamType::Hex32)], &mut 1)?;
+                if let Some(id) = ctx.session_init {
+                    printer.print_param("ORIGINAL_SESSION_ID", &format!("{:#x}", id));
+                }
+                return Ok(());
+            }
+            print_rest(printer, pkt, 1, "Unknown");
+            Ok(())
+        }
+    ),
+    SessionStatusNtf(SessionStatusNtf): (SESSION, SESSION_STATUS, NTF, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            let reasons: &HashMap<u8, &str> = match ctx.version {
+                UciVersion::V1 => &SESSION_REASON_CODES,
+                UciVersion::V2 => &SESSION_REASON_CODES_V2,
+            };
+            let mut offset = 0;
+            _print_static(printer, pkt, session_fields(ctx), &mut offset)?;
+            _print_static(printer, pkt, &[
+                          Field("SESSION_STATE", ParamType::Map8(&SESSION_STATE_CODES)),
+                          Field("REASON_CODE", ParamType::Map8(reasons)),], &mut offset)?;
+            print_session_of_handle(printer, pkt, 0, ctx);
+            Ok(())
+        }
+    ),
+    SessionSetAppConfigCmd(SetAppConfigCmd): (SESSION, SESSION_SET_APP_CONFIG, CMD, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            print_session(printer, pkt, ctx)?;
+            print_config(printer, pkt, 4, ctx.app_conf_params(leading_session(pkt)), None)?;
+            Ok(())
+        }
+    ),
+    SessionSetAppConfigRsp(SetConfigRsp): (SESSION, SESSION_SET_APP_CONFIG, RSP, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            print_config_status(printer, pkt, ctx.app_conf_params(ctx.app_config_session), None)
+        }
+    ),
+    SessionGetAppConfigCmd(GetAppConfigCmd): (SESSION, SESSION_GET_APP_CONFIG, CMD, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            print_session(printer, pkt, ctx)?;
+            print_config_ids(printer, pkt, 4, ctx.app_conf_params(leading_session(pkt)), None)
+        }
+    ),
+    SessionGetAppConfigRsp(ConfigRsp): (SESSION, SESSION_GET_APP_CONFIG, RSP, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            print_status_or(printer, pkt, || print_config(printer, pkt, 1, ctx.app_conf_params(ctx.app_config_session), None))
+        }
+    ),
+
+    SessionDeinitCmd(SessionCmd): (SESSION, SESSION_DEINIT, CMD, ctx print_session),
+    SessionDeinitRsp(StatusOnly): (SESSION, SESSION_DEINIT, RSP, print_status_only),
+    SessionGetCountCmd(Empty): (SESSION, SESSION_GET_COUNT, CMD, print_empty),
+    SessionGetCountRsp(SessionGetCountRsp): (SESSION, SESSION_GET_COUNT, RSP,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &[
+                         Field("STATUS", ParamType::Map8(&STATUS_CODES)),
+                         Field("SESSION_COUNT", ParamType::Dec8),])
+        }
+    ),
+    SessionGetStateCmd(SessionCmd): (SESSION, SESSION_GET_STATE, CMD, ctx print_session),
+    SessionGetStateRsp(SessionGetStateRsp): (SESSION, SESSION_GET_STATE, RSP,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &[
+                         Field("STATUS", ParamType::Map8(&STATUS_CODES)),
+                         Field("SESSION_STATE", ParamType::Map8(&SESSION_STATE_CODES)),])
+        }
+    ),
+    SessionUpdateControllerMulticastListCmd(SessionUpdateControllerMulticastListCmd): (SESSION, SESSION_UPDATE_CONTROLLER_MULTICAST_LIST, CMD, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            print_session(printer, pkt, ctx)?;
+            let mut offset = 4;
+            _print_static(printer, pkt, &[
+                          Field("ACTION", ParamType::Table8(&[
+                              (0u8, "Add"), (1u8, "Delete"),
+                              (2u8, "Add with 16-octet sub-session key"),
+                              (3u8, "Add with 32-octet sub-session key"),
+                          ])),
+                          Field("NUMBER_OF_CONTROLEES", ParamType::Dec8),], &mut offset)?;
+            let key = match pkt.get(4) {
+                2 => Some(Field("SUB_SESSION_KEY", ParamType::HexArray(16))),
+                3 => Some(Field("SUB_SESSION_KEY", ParamType::HexArray(32))),
+                _ => None,
+            };
+            let mut fields = vec![Field("SHORT_ADDRESS", ParamType::Hex16), Field("SUB_SESSION_ID", ParamType::Hex32)];
+            fields.extend(key);
+            print_list(printer, pkt, &mut offset, "Controlees", "Controlee", pkt.get(5), &fields)
+        }
+    ),
+    SessionUpdateControllerMulticastListRsp(StatusOnly): (SESSION, SESSION_UPDATE_CONTROLLER_MULTICAST_LIST, RSP, print_status_only),
+    SessionUpdateControllerMulticastListNtf(SessionUpdateControllerMulticastListNtf): (SESSION, SESSION_UPDATE_CONTROLLER_MULTICAST_LIST, NTF, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            print_session(printer, pkt, ctx)?;
+            let mut offset = 4;
+            match ctx.version {
+                UciVersion::V1 => {
+                    _print_static(printer, pkt, &[
+                                  Field("REMAINING_MULTICAST_LIST_SIZE", ParamType::Dec8),
+                                  Field("NUMBER_OF_CONTROLEES", ParamType::Dec8),], &mut offset)?;
+                    print_list(printer, pkt, &mut offset, "Controlees", "Controlee", pkt.get(5), &[
+                               Field("MAC_ADDRESS", ParamType::Hex16),
+                               Field("SUB_SESSION_ID", ParamType::Hex32),
+                               Field("STATUS", ParamType::Map8(&MULTICAST_STATUS_CODES)),])
+                }
+                /* the remaining list size and sub-session IDs were dropped in 2.0 */
+                UciVersion::V2 => {
+                    _print_static(printer, pkt, &[Field("NUMBER_OF_CONTROLEES", ParamType::Dec8)], &mut offset)?;
+                    print_list(printer, pkt, &mut offset, "Controlees", "Controlee", pkt.get(4), &[
+                               Field("MAC_ADDRESS", ParamType::Hex16),
+                               Field("STATUS", ParamType::Map8(&MULTICAST_STATUS_CODES)),])
+                }
+            }
+        }
+    ),
+
+    TestConfigSetCmd: (TEST, TEST_CONFIG_SET, CMD, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            print_session(printer, pkt, ctx)?;
+            print_config(printer, pkt, 4, &TEST_CONF_PARAMS, None)
+        }
+    ),
+    TestConfigSetRsp: (TEST, TEST_CONFIG_SET, RSP,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_config_status(printer, pkt, &TEST_CONF_PARAMS, None)
+        }
+    ),
+    TestConfigGetCmd: (TEST, TEST_CONFIG_GET, CMD, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            print_session(printer, pkt, ctx)?;
+            print_config_ids(printer, pkt, 4, &TEST_CONF_PARAMS, None)
+        }
+    ),
+    TestConfigGetRsp: (TEST, TEST_CONFIG_GET, RSP,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_status_or(printer, pkt, || print_config(printer, pkt, 1, &TEST_CONF_PARAMS, None))
+        }
+    ),
+    TestPeriodicTxCmd: (TEST, TEST_PERIODIC_TX, CMD, print_psdu_cmd),
+    TestPeriodicTxRsp: (TEST, TEST_PERIODIC_TX, RSP, print_status_only),
+    TestPeriodicTxNtf: (TEST, TEST_PERIODIC_TX, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_status_only(printer, pkt)?;
+            print_rest(printer, pkt, 1, "VENDOR_SPECIFIC_DATA");
+            Ok(())
+        }
+    ),
+    TestPerRxCmd: (TEST, TEST_PER_RX, CMD, print_psdu_cmd),
+    TestPerRxRsp: (TEST, TEST_PER_RX, RSP, print_status_only),
+    TestPerRxNtf: (TEST, TEST_PER_RX, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            let mut offset = 0;
+            _print_static(printer, pkt, &[
+                          Field("STATUS", ParamType::Map8(&STATUS_CODES)),
+                          Field("ATTEMPTS", ParamType::Dec32),
+                          Field("ACQ_DETECT", ParamType::Dec32),
+                          Field("ACQ_REJECT", ParamType::Dec32),
+                          Field("RX_FAIL", ParamType::Dec32),
+                          Field("SYNC_CIR_READY", ParamType::Dec32),
+                          Field("SFD_FAIL", ParamType::Dec32),
+                          Field("SFD_FOUND", ParamType::Dec32),
+                          Field("PHR_DEC_ERROR", ParamType::Dec32),
+                          Field("PHR_BIT_ERROR", ParamType::Dec32),
+                          Field("PSDU_DEC_ERROR", ParamType::Dec32),
+                          Field("PSDU_BIT_ERROR", ParamType::Dec32),
+                          Field("STS_FOUND", ParamType::Dec32),
+                          Field("EOF", ParamType::Dec32),], &mut offset)?;
+            print_rest(printer, pkt, offset, "VENDOR_SPECIFIC_DATA");
+            Ok(())
+        }
+    ),
+    TestRxCmd: (TEST, TEST_RX, CMD, print_empty),
+    TestRxRsp: (TEST, TEST_RX, RSP, print_status_only),
+    TestRxNtf: (TEST, TEST_RX, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            let mut offset = 0;
+            _print_static(printer, pkt, &[
+                          Field("STATUS", ParamType::Map8(&STATUS_CODES)),
+                          Field("RX_DONE_TS_INT", ParamType::Dec32),
+                          Field("RX_DONE_TS_FRAC", ParamType::Dec16),
+                          Field("AOA_AZIMUTH", ParamType::Angle(7)),
+                          Field("AOA_ELEVATION", ParamType::Angle(7)),
+                          Field("TOA_GAP", ParamType::Dec8),
+                          Field("PHR", ParamType::Hex16),], &mut offset)?;
+            print_psdu(printer, pkt, offset)
+        }
+    ),
+    TestLoopbackCmd: (TEST, TEST_LOOPBACK, CMD, print_psdu_cmd),
+    TestLoopbackRsp: (TEST, TEST_LOOPBACK, RSP, print_status_only),
+    TestLoopbackNtf: (TEST, TEST_LOOPBACK, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            let mut offset = 0;
+            _print_static(printer, pkt, &[
+                          Field("STATUS", ParamType::Map8(&STATUS_CODES)),
+                          Field("TX_TS_INT", ParamType::Dec32),
+                          Field("TX_TS_FRAC", ParamType::Dec16),
+                          Field("RX_TS_INT", ParamType::Dec32),
+                          Field("RX_TS_FRAC", ParamType::Dec16),
+                          Field("AOA_AZIMUTH", ParamType::Angle(7)),
+                          Field("AOA_ELEVATION", ParamType::Angle(7)),
+                          Field("PHR", ParamType::Hex16),], &mut offset)?;
+            print_psdu(printer, pkt, offset)
+        }
+    ),
+    TestStopSessionCmd: (TEST, TEST_STOP_SESSION, CMD, print_empty),
+    TestStopSessionRsp: (TEST, TEST_STOP_SESSION, RSP, print_status_only),
+    TestSsTwrCmd: (TEST, TEST_SS_TWR, CMD, print_empty),
+    TestSsTwrRsp: (TEST, TEST_SS_TWR, RSP, print_status_only),
+    TestSsTwrNtf: (TEST, TEST_SS_TWR, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            let mut offset = 0;
+            _print_static(printer, pkt, &[
+                          Field("STATUS", ParamType::Map8(&STATUS_CODES)),
+                          Field("MEASUREMENT", ParamType::Int32),], &mut offset)?;
+            print_rest(printer, pkt, offset, "VENDOR_SPECIFIC_DATA");
+            Ok(())
+        }
+    ),
+
+    NxpCoreDeviceInitCmd: (PROPRIETARY, NXP_CORE_DEVICE_INIT, CMD,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &NXP_CORE_DEVICE_INIT_CMD_FIELDS)
+        }
+    ),
+    NxpCoreDeviceInitRsp: (PROPRIETARY, NXP_CORE_DEVICE_INIT, RSP, print_status_only),
+
+    NxpSetCalibrationCmd: (PROPRIETARY, NXP_SET_CALIBRATION, CMD, print_calibration_write),
+    NxpSetCalibrationRsp: (PROPRIETARY, NXP_SET_CALIBRATION, RSP, print_status_only),
+    NxpGetCalibrationCmd: (PROPRIETARY, NXP_GET_CALIBRATION, CMD, print_calibration_read),
+    NxpGetCalibrationRsp: (PROPRIETARY, NXP_GET_CALIBRATION, RSP, ctx print_calibration_value),
+
+    /* SR1xx calibration data store, same layout as SET/GET_CALIBRATION */
+    NxpWriteCalibDataCmd: (PROPRIETARY_A, NXP_WRITE_CALIB_DATA, CMD, print_calibration_write),
+    NxpWriteCalibDataRsp: (PROPRIETARY_A, NXP_WRITE_CALIB_DATA, RSP, print_status_only),
+    NxpReadCalibDataCmd: (PROPRIETARY_A, NXP_READ_CALIB_DATA, CMD, print_calibration_read),
+    NxpReadCalibDataRsp: (PROPRIETARY_A, NXP_READ_CALIB_DATA, RSP, ctx print_calibration_value),
+
+    NxpDbgLogConfigCmd: (PROPRIETARY_B, NXP_DBG_LOG_CONFIG, CMD,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &NXP_DBG_LOG_CONFIG_CMD_FIELDS)
+        }
+    ),
+    NxpDbgLogConfigRsp: (PROPRIETARY_B, NXP_DBG_LOG_CONFIG, RSP, print_status_only),
+    NxpCirCaptureCmd: (PROPRIETARY_B, NXP_CIR_CAPTURE, CMD,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &NXP_CIR_CAPTURE_CMD_FIELDS)
+        }
+    ),
+    NxpCirCaptureRsp: (PROPRIETARY_B, NXP_CIR_CAPTURE, RSP, print_status_only),
+    NxpCirCaptureNtf: (PROPRIETARY_B, NXP_CIR_CAPTURE, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            let mut offset = 0;
+            _print_static(printer, pkt, &[
+                          Field("SESSION_ID", ParamType::Hex32),
+                          Field("RX_ANTENNA_ID", ParamType::Hex8),
+                          Field("CIR_LEN", ParamType::Dec16),], &mut offset)?;
+            let cir = LittleEndian::read_u16(pkt.slice(offset - 2, 2));
+            print_sized(printer, pkt, &mut offset, "CIR_DATA", cir.into())?;
+            print_rest(printer, pkt, offset, "Unknown");
+            Ok(())
+        }
+    ),
+
+    NxpThermalStatusNtf: (PROPRIETARY_F, NXP_THERMAL_STATUS, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &[
+                         Field("Thermal state", ParamType::Table8(&[(0u8, "Normal"), (1u8, "Throttled"), (2u8, "Thermal runaway")])),
+                         Field("Temperature", ParamType::Int8),])
+        }
+    ),
+
+    NxpSeCommErrorNtf: (PROPRIETARY, NXP_SE_COMM_ERROR, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &[Field("STATUS", ParamType::Map8(&STATUS_CODES)),
+                Field("CLA_INS", ParamType::Hex16),
+                Field("T=1_STATUS_CODE", ParamType::Hex16)])
+        }
+    ),
+    NxpBindingStatNtf: (PROPRIETARY, NXP_BINDING_STAT, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &BINDING_STATUS_FIELDS)
+        }
+    ),
+    NxpSeDoBindCmd: (PROPRIETARY, NXP_SE_DO_BIND, CMD, print_empty),
+    NxpSeDoBindRsp: (PROPRIETARY, NXP_SE_DO_BIND, RSP, print_status_only),
+    NxpSeDoBindNtf: (PROPRIETARY, NXP_SE_DO_BIND, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &[
+                         Field("STATUS", ParamType::Map8(&STATUS_CODES)),
+                         Field("Remaining binding count", ParamType::Dec8)])
+        }
+    ),
+    NxpSeGetBindingCntCmd: (PROPRIETARY, NXP_SE_GET_BINDING_CNT, CMD, print_empty),
+    NxpSeGetBindingCntRsp: (PROPRIETARY, NXP_SE_GET_BINDING_CNT, RSP,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_status_or(printer, pkt, || {
+                _print_static(printer, pkt, &[
+                              Field("Binding state", ParamType::Table8(&BINDING_STATES)),
+                              Field("SE binding count", ParamType::Dec8),
+                              Field("UWBS binding count", ParamType::Dec8),], &mut 1)
+            })
+        }
+    ),
+    NxpSeGetBindingStatCmd: (PROPRIETARY, NXP_SE_GET_BINDING_STAT, CMD, print_empty),
+    NxpSeGetBindingStatRsp: (PROPRIETARY, NXP_SE_GET_BINDING_STAT, RSP, print_status_only),
+    NxpSeGetBindingStatNtf: (PROPRIETARY, NXP_SE_GET_BINDING_STAT, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &BINDING_STATUS_FIELDS)
+        }
+    ),
+    NxpTestLoopCmd: (PROPRIETARY, NXP_TEST_LOOP, CMD,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &NXP_TEST_LOOP_CMD_FIELDS)
+        }
+    ),
+    NxpTestLoopRsp: (PROPRIETARY, NXP_TEST_LOOP, RSP, print_status_only),
+    NxpTestLoopNtf: (PROPRIETARY, NXP_TEST_LOOP, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_static(printer, pkt, &[
+                         Field("STATUS", ParamType::Map8(&STATUS_CODES)),
+                         Field("Loop count", ParamType::Dec16),
+                         Field("Loop pass count", ParamType::Dec16)])
+        }
+    ),
+    NxpTestConnectivityCmd: (PROPRIETARY, NXP_TEST_CONNECTIVITY, CMD, print_empty),
+    NxpTestConnectivityRsp: (PROPRIETARY, NXP_TEST_CONNECTIVITY, RSP, print_status_only),
+    NxpTestConnectivityNtf: (PROPRIETARY, NXP_TEST_CONNECTIVITY, NTF,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            let mut offset = 0;
+            _print_static(printer, pkt, &[Field("STATUS", ParamType::Map8(&STATUS_CODES))], &mut offset)?;
+            print_rest(printer, pkt, offset, "SE status");
+            Ok(())
+        }
+    ),
+    NxpGetIntfStatCmd: (PROPRIETARY, NXP_GET_INTF_STAT, CMD, print_empty),
+    NxpGetIntfStatRsp: (PROPRIETARY, NXP_GET_INTF_STAT, RSP,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_status_or(printer, pkt, || {
+                let mut offset = 1;
+                _print_static(printer, pkt, &[
+                              Field("Interface", ParamType::Table8(&[(0u8, "SPI"), (1u8, "I2C"), (2u8, "UART")])),
+                              Field("Tx frame count", ParamType::Dec32),
+                              Field("Rx frame count", ParamType::Dec32),
+                              Field("Error count", ParamType::Dec32),], &mut offset)?;
+                print_rest(printer, pkt, offset, "Unknown");
+                Ok(())
+            })
+        }
+    ),
+
+    RangeStartRsp(StatusOnly): (RANGING, RANGE_START, RSP, print_status_only),
+    RangeStartCmd(SessionCmd): (RANGING, RANGE_START, CMD, ctx print_session),
+    RangeStopCmd(SessionCmd): (RANGING, RANGE_STOP, CMD, ctx print_session),
+    RangeStopRsp(StatusOnly): (RANGING, RANGE_STOP, RSP, print_status_only),
+    GetRangingCountCmd(SessionCmd): (RANGING, GET_RANGING_COUNT, CMD, ctx print_session),
+    GetRangingCountRsp(GetRangingCountRsp): (RANGING, GET_RANGING_COUNT, RSP,
+        |printer: &dyn Printer, pkt: &Packet| -> Result<(), UciPacketParseError> {
+            print_status_or(printer, pkt, || _print_static(printer, pkt, &[Field("COUNT", ParamType::Dec32)], &mut 1))
+        }
+    ),
+    DataMessageSnd: (DATA, DATA_MESSAGE_SND, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            let mut offset = 0;
+            _print_static(printer, pkt, &DATA_MESSAGE_SND_FIELDS, &mut offset)?;
+            print_session_of_handle(printer, pkt, 0, ctx);
+            print_app_data(printer, pkt, offset)
+        }
+    ),
+    DataMessageRcv: (DATA, DATA_MESSAGE_RCV, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            let mut offset = 0;
+            _print_static(printer, pkt, &[
+                          Field("SESSION_HANDLE", ParamType::Hex32),
+                          Field("STATUS", ParamType::Map8(&STATUS_CODES)),
+                          Field("SOURCE_ADDRESS", ParamType::HexArray(8)),
+                          Field("SEQUENCE_NUMBER", ParamType::Dec16),], &mut offset)?;
+            print_session_of_handle(printer, pkt, 0, ctx);
+            print_app_data(printer, pkt, offset)
+        }
+    ),
+
+    RangeDataNtf(RangeDataNtf): (RANGING, RANGE_DATA, NTF, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            let len = pkt.len();
+
+            if len < 25 {
+                return Err(UciPacketParseError::new("mismatch length"));
+            }
+
+            let nr: u8 = pkt.get(24);
+            let mut offset: usize = 25;
+            let mac_type: range_data::MacType = pkt.get(15).into();
+            let mut report_type: range_data::ReportType = pkt.get(13).into();
+
+            /* some firmwares report OWR for AoA as type 0x02; tell it from DL-TDoA by its size */
+            let owr_aoa_len: usize = owr_aoa_fields(mac_type).iter().map(|f| f.size()).sum();
+            let owr_aoa_as_dl_tdoa = matches!(report_type, range_data::ReportType::DownTdoa)
+                && nr > 0 && len - offset == nr as usize * owr_aoa_len;
+            if owr_aoa_as_dl_tdoa {
+                report_type = range_data::ReportType::OwrAoa;
+            }
+
+            let arr = [
+                Field("Sequence number", ParamType::Dec32),
+                match ctx.version {
+                    UciVersion::V1 => Field("Session ID", ParamType::Hex32),
+                    UciVersion::V2 => Field("Session Handle", ParamType::Hex32),
+                },
+                match ctx.version {
+                    UciVersion::V1 => Field("", ParamType::RFU(1)),
+                    UciVersion::V2 => Field("RCR indication", ParamType::Hex8),
+                },
+                Field("Ranging interval", ParamType::Dec32),
+                Field("Ranging type", ParamType::Table8(&[(0u8, "TDoA"), (1u8, "TWR"), (2u8, "Down TDoA"), (3u8, "OWR AoA")])),
+                Field("", ParamType::RFU(1)),
+                Field("Mac addressing mode", ParamType::Table8(&[(0u8, "short"), (1u8, "long")])),
+                Field("", ParamType::RFU(8)),
+                Field("Number of Ranging Measurements", ParamType::Dec8),
+            ];
+            print_static(printer, pkt, &arr)?;
+            print_session_of_handle(printer, pkt, 4, ctx);
+            if owr_aoa_as_dl_tdoa {
+                printer.print_comment("Reports sized as OWR AoA measurements");
+            }
+
+            printer.begin_list("Reports");
+            for i in 0..nr {
+                printer.begin_item(&format!("Report {}", i));
+                match report_type {
+                    range_data::ReportType::Twr => {
+                        print_range_data_twr(printer, pkt, &mut offset, mac_type, ctx.version)?;
+                    }
+                    range_data::ReportType::Tdoa => {
+                        print_range_data_tdoa(printer, pkt, &mut offset, mac_type)?;
+                    }
+                    range_data::ReportType::DownTdoa => {
+                        print_range_data_dl_tdoa(printer, pkt, &mut offset, mac_type)?;
+                    }
+                    range_data::ReportType::OwrAoa => {
+                        print_range_data_owr_aoa(printer, pkt, &mut offset, mac_type)?;
+                    }
+                    _ => {
+                        return Err(UciPacketParseError::new(&format!("unsupported measurement type {}", report_type as u8)));
+                    }
+                }
+                printer.end_item();
+            }
+            printer.end_list();
+
+            if offset < len && ctx.nxp {
+                print_range_data_nxp_ext(printer, pkt, &mut offset)?;
+            }
+            print_rest(printer, pkt, offset, "Unknown");
+            Ok(())
+        }
+    ),
+    /* CCC ranging result: a single TWR measurement, without a MAC address */
+    RangeCccDataNtf(RangeCccDataNtf): (RANGING, RANGE_CCC_DATA, NTF, ctx
+        |printer: &dyn Printer, pkt: &Packet, ctx: &Context| -> Result<(), UciPacketParseError> {
+            let mut offset = 0;
+            _print_static(printer, pkt, session_fields(ctx), &mut offset)?;
+            print_session_of_handle(printer, pkt, 0, ctx);
+            _print_static(printer, pkt, &[
+                          Field("RANGING_STATUS", ParamType::Map8(&STATUS_CODES)),
+                          Field("STS_INDEX", ParamType::Hex32),
+                          Field("RR_INDEX", ParamType::Dec16),
+                          Field("DISTANCE", ParamType::Dec16),
+                          Field("UNCERTAINTY_ANCHOR_FOM", ParamType::Dec8),
+                          Field("UNCERTAINTY_INITIATOR_FOM", ParamType::Dec8),
+                          Field("CCM_TAG", ParamType::HexArray(8)),
+                          Field("AOA_AZIMUTH", ParamType::Angle(7)),
+                          Field("AOA_AZIMUTH_FOM", ParamType::Dec8),
+                          Field("AOA_ELEVATION", ParamType::Angle(7)),
+                          Field("AOA_ELEVATION_FOM", ParamType::Dec8),], &mut offset)?;
+            print_rest(printer, pkt, offset, "Unknown");
+            Ok(())
+        }
+    ),
+}
+
+/* one JSON object for the packet, including the decoding error if any */
+fn json_packet(pkt: &Packet, info: &FrameInfo, ctx: &Context) -> String {
+    let id = PacketId::from(pkt);
+    let mut printer = JsonPrinter::new(id.2, id.0, id.1);
+    for (k, v) in info.meta() {
+        printer.set_meta(k, v);
+    }
+    let ret = print_packet(&printer, pkt, ctx);
+    printer.finish(ret.err().map(|e| e.to_string()).as_deref())
+}
+
+fn print_packet(printer: &dyn Printer, pkt: &Packet, ctx: &Context) -> Result<(), UciPacketParseError> {
+    let id = PacketId::from(pkt);
+    match PACKETS.get(&id) {
+        Some(desc) => {
+            printer.print_id(desc.name);
+            (desc.print)(printer, pkt, ctx)
+        }
+        None => Err(UciPacketParseError::new(&format!("unrecognized packet {} => payload: {}", pkt, &print_hexarr(pkt, 0, pkt.len())))),
+    }
+}
+
+/* decodes a complete (reassembled) packet; the variant tells the packet, the Message holds its fields */
+pub fn decode_packet(pkt: &Packet) -> Result<UciMessage, UciPacketParseError> {
+    decode_in(pkt, &Context::default())
+}
+
+/* same as decode_packet(), with the layouts of UCI `version` */
+pub fn decode_packet_with(pkt: &Packet, version: UciVersion) -> Result<UciMessage, UciPacketParseError> {
+    decode_in(pkt, &Context::with_version(version))
+}
+
+fn decode_in(pkt: &Packet, ctx: &Context) -> Result<UciMessage, UciPacketParseError> {
+    let (desc, msg) = collect_in(pkt, ctx)?;
+    (desc.wrap)(msg)
+}
+
+/* the fields of a complete packet, as its decoder prints them; the typed payloads are read from them */
+fn collect_in(pkt: &Packet, ctx: &Context) -> Result<(&'static PacketDesc, Message), UciPacketParseError> {
+    if pkt.pbf() {
+        return Err(UciPacketParseError::new(&format!("segmented packet {}: reassemble it first", pkt)));
+    }
+    let desc = match PACKETS.get(&PacketId::from(pkt)) {
+        Some(desc) => desc,
+        None => return Err(UciPacketParseError::new(&format!("unrecognized packet {}", pkt))),
+    };
+    let collector = Collector::new();
+    (desc.print)(&collector, pkt, ctx)?;
+    let (params, comments) = collector.into_parts();
+    let id = PacketId::from(pkt);
+    Ok((desc, Message {
+        name: desc.name,
+        mt: id.2,
+        gid: id.0,
+        oid: id.1,
+        params,
+        comments,
+    }))
+}
+
+pub fn decode(bytes: &[u8]) -> Result<UciMessage, UciPacketParseError> {
+    decode_packet(&Packet::from_bytes(bytes)?)
+}
+
+pub fn decode_with(bytes: &[u8], version: UciVersion) -> Result<UciMessage, UciPacketParseError> {
+    decode_packet_with(&Packet::from_bytes(bytes)?, version)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Direction {
+    HostToUwbs,
+    UwbsToHost,
+}
+
+impl Direction {
+    /* commands and sent data travel host -> UWBS, everything else the other way */
+    fn of_id(id: &PacketId) -> Direction {
+        if id.2 == mt::CMD || (id.2 == mt::DATA && id.0 == dpf::DATA_MESSAGE_SND) {
+            Direction::HostToUwbs
+        } else {
+            Direction::UwbsToHost
+        }
+    }
+
+    /* direction implied by the message type */
+    pub fn of(pkt: &Packet) -> Direction {
+        Direction::of_id(&PacketId::from(pkt))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::HostToUwbs => "HOST_TO_UWBS",
+            Direction::UwbsToHost => "UWBS_TO_HOST",
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Direction::HostToUwbs => write!(f, ">"),
+            Direction::UwbsToHost => write!(f, "<"),
+        }
+    }
+}
+
+/* Capture metadata of a frame, when the input provides it */
+#[derive(Clone, Debug, Default)]
+pub struct FrameInfo {
+    pub timestamp: Option<String>,
+    pub direction: Option<Direction>,
+}
+
+impl FrameInfo {
+    fn is_empty(&self) -> bool {
+        self.timestamp.is_none() && self.direction.is_none()
+    }
+
+    /* key/value pairs for structured output */
+    fn meta(&self) -> Vec<(&str, &str)> {
+        let mut meta = Vec::new();
+        if let Some(ts) = &self.timestamp {
+            meta.push(("timestamp", ts.as_str()));
+        }
+        if let Some(dir) = self.direction {
+            meta.push(("direction", dir.name()));
+        }
+        meta
+    }
+}
+
+impl fmt::Display for FrameInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ts = self.timestamp.as_deref().unwrap_or("");
+        match self.direction {
+            Some(dir) if !ts.is_empty() => write!(f, "{} {}", ts, dir),
+            Some(dir) => write!(f, "{}", dir),
+            None => write!(f, "{}", ts),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+/* Stream parser: keeps state across packets of a trace */
+#[derive(Default)]
+pub struct Parser {
+    reassembler: Reassembler,
+    format: OutputFormat,
+    context: Context,
+}
+
+impl Parser {
+    pub fn new() -> Parser {
+        Parser::with_format(OutputFormat::Text)
+    }
+
+    pub fn with_format(format: OutputFormat) -> Parser {
+        Parser {
+            reassembler: Reassembler::new(),
+            format,
+            context: Context::default(),
+        }
+    }
+
+    /* decode as `version` instead of detecting it from CORE_GET_DEVICE_INFO_RSP */
+    pub fn set_version(&mut self, version: UciVersion) {
+        self.context = Context::with_version(version);
+    }
+
+    /* a line that is not a decoded packet */
+    fn report(&self, key: &str, msg: &str, info: &FrameInfo) {
+        match self.format {
+            OutputFormat::Text if info.is_empty() => println!("{}", msg),
+            OutputFormat::Text => println!("{} {}", info, msg),
+            OutputFormat::Json => println!("{}", JsonPrinter::message(&info.meta(), key, msg)),
+        }
+    }
+
+    fn print(&self, pkt: &Packet, info: &FrameInfo) {
+        match self.format {
+            OutputFormat::Text => {
+                /* the message name follows on the same line */
+                if !info.is_empty() {
+                    print!("{} ", info);
+                }
+                if let Err(e) = print_packet(&BasicPrinter, pkt, &self.context) {
+                    println!("{}", e);
+                }
+            }
+            OutputFormat::Json => println!("{}", json_packet(pkt, info, &self.context)),
+        }
+    }
+
+    pub fn parse(&mut self, s: String) {
+        self.parse_frame(s, &FrameInfo::default());
+    }
+
+    /* a frame extracted from a capture, printed along with its metadata */
+    pub fn parse_frame(&mut self, s: String, info: &FrameInfo) {
+        self.push(to_packet(&s), info);
+    }
+
+    pub fn parse_bytes(&mut self, bytes: &[u8], info: &FrameInfo) {
+        self.push(Packet::from_bytes(bytes), info);
+    }
+
+    fn push(&mut self, pkt: Result<Packet, UciPacketParseError>, info: &FrameInfo) {
+        let pkt = match pkt {
+            Ok(pkt) => pkt,
+            Err(e) => {
+                self.report("error", &e.to_string(), info);
+                return;
+            }
+        };
+
+        if pkt.pbf() {
+            self.report("comment", &format!("segment {} buffered", pkt), info);
+        }
+        for r in self.reassembler.push(pkt) {
+            match r {
+                Ok(pkt) => {
+                    self.print(&pkt, info);
+                    self.context.update(&pkt);
+                }
+                Err(e) => self.report("error", &e.to_string(), info),
+            }
+        }
+    }
+
+    /* end of stream */
+    pub fn finish(&mut self) {
+        for e in self.reassembler.flush() {
+            self.report("error", &e.to_string(), &FrameInfo::default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Param;
+
+    fn packets(r: Vec<Result<Packet, UciPacketParseError>>) -> Vec<Vec<u8>> {
+        r.into_iter().map(|p| p.unwrap().bytes().to_vec()).collect()
+    }
+
+    #[test]
+    fn reassemble_segments() {
+        let mut r = Reassembler::new();
+        assert!(r.push(Packet::from_hex("31030003aabbcc").unwrap()).is_empty());
+        assert_eq!(packets(r.push(Packet::from_hex("21030002ddee").unwrap())),
+                   vec![vec![0x21, 0x03, 0x00, 0x05, 0xaa, 0xbb, 0xcc, 0xdd, 0xee]]);
+        assert!(r.flush().is_empty());
+    }
+
+    #[test]
+    fn reassemble_long_message() {
+        let mut r = Reassembler::new();
+        let id = PacketId(gid::SESSION, oid::SESSION_SET_APP_CONFIG, mt::CMD);
+        assert!(r.push(Packet::build(&id, true, &[1; 200])).is_empty());
+        let pkt = r.push(Packet::build(&id, false, &[2; 100])).pop().unwrap().unwrap();
+        assert_eq!(&pkt.bytes()[..4], &[0x21, 0x03, 0x01, 0x2c]);
+        assert_eq!(pkt.len(), 300);
+        assert!(!pkt.pbf());
+
+        /* data packets keep their 16-bit length */
+        let id = PacketId(dpf::DATA_MESSAGE_SND, 0, mt::DATA);
+        assert!(r.push(Packet::build(&id, true, &[1; 300])).is_empty());
+        let pkt = r.push(Packet::build(&id, false, &[2; 20])).pop().unwrap().unwrap();
+        assert_eq!(&pkt.bytes()[..4], &[0x01, 0x00, 0x40, 0x01]);
+        assert_eq!(pkt.len(), 320);
+    }
+
+    #[test]
+    fn reassemble_interleaved() {
+        let mut r = Reassembler::new();
+        assert!(r.push(Packet::from_hex("31030001aa").unwrap()).is_empty());
+        /* a response travels the other way and does not interrupt the command */
+        assert_eq!(packets(r.push(Packet::from_hex("4100000100").unwrap())),
+                   vec![vec![0x41, 0x00, 0x00, 0x01, 0x00]]);
+        /* another command drops the unfinished one */
+        let ret = r.push(Packet::from_hex("21040001bb").unwrap());
+        assert_eq!(ret.len(), 2);
+        assert!(ret[0].as_ref().unwrap_err().to_string().contains("interleaved segment"));
+        assert_eq!(ret[1].as_ref().unwrap().bytes(), &[0x21, 0x04, 0x00, 0x01, 0xbb]);
+    }
+
+    #[test]
+    fn reassemble_orphaned() {
+        let mut r = Reassembler::new();
+        assert!(r.push(Packet::from_hex("31030001aa").unwrap()).is_empty());
+        assert!(r.push(Packet::from_hex("7e1100020102").unwrap()).is_empty());
+        let errors = r.flush();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|e| e.to_string().contains("orphaned segment")));
+        assert!(r.flush().is_empty());
+    }
+
+    /* `hex` decoded as a field of type `t` */
+    fn value(t: ParamType, hex: &str) -> Value {
+        let pkt = Packet::from_hex(&format!("0000{:02x}00{}", hex.len() / 2, hex)).unwrap();
+        print_field(&Field("", t), &pkt, 0, pkt.len()).unwrap()
+    }
+
+    #[test]
+    fn fixed_point() {
+        /* AoA, Q9.7 degrees */
+        assert_eq!(value(ParamType::Angle(7), "80ff"), Value::Fixed(-1.0, Some("deg"), 0xff80));
+        assert_eq!(value(ParamType::Angle(7), "c0f9"), Value::Fixed(-12.5, Some("deg"), 0xf9c0));
+        assert_eq!(value(ParamType::Angle(7), "405a"), Value::Fixed(180.5, Some("deg"), 0x5a40));
+        assert_eq!(value(ParamType::Angle(7), "0080"), Value::Fixed(-256.0, Some("deg"), 0x8000));
+        assert_eq!(value(ParamType::Angle(7), "80ff").to_string(), "-1 deg (0xff80)");
+        /* RSSI, Q7.1 */
+        assert_eq!(value(ParamType::Q8(1), "b1"), Value::Fixed(-39.5, None, 0xb1));
+        assert_eq!(value(ParamType::Q8(1), "7f"), Value::Fixed(63.5, None, 0x7f));
+        assert_eq!(value(ParamType::Q8(1), "b1").to_string(), "-39.5 (0xb1)");
+        /* CFO, Q5.11 */
+        assert_eq!(value(ParamType::Q16(11), "00fc"), Value::Fixed(-0.5, None, 0xfc00));
+        assert_eq!(value(ParamType::Q16(11), "0128"), Value::Fixed(5.000488281250, None, 0x2801));
+        assert_eq!(value(ParamType::Q16(11), "ffff"), Value::Fixed(-1.0 / 2048.0, None, 0xffff));
+        /* RSSI, Q8.8 */
+        assert_eq!(value(ParamType::Q16(8), "80b0"), Value::Fixed(-79.5, None, 0xb080));
+        assert!(matches!(value(ParamType::Q8(1), "b1b1"), Value::Invalid(_)));
+
+        /* UCI 2.0 TWR reports carry the RSSI in their first padding byte */
+        let hex = format!("{}b1{}", &TWR_SHORT[..TWR_SHORT.len() - 24], "00".repeat(11));
+        let msg = message_in(&Packet::from_hex(&hex).unwrap(), &Context::with_version(UciVersion::V2));
+        assert_eq!(field(&report_params(&msg)[0], "RSSI").unwrap().value.as_f64(), Some(-39.5));
+    }
+
+    #[test]
+    fn signed_integers() {
+        assert_eq!(value(ParamType::Int8, "ff"), Value::Int(-1));
+        assert_eq!(value(ParamType::Int8, "80"), Value::Int(-128));
+        assert_eq!(value(ParamType::Int8, "7f"), Value::Int(127));
+        assert_eq!(value(ParamType::Int16, "0080"), Value::Int(-32768));
+        assert_eq!(value(ParamType::Int16, "feff"), Value::Int(-2));
+        assert_eq!(value(ParamType::Int32, "ffffffff"), Value::Int(-1));
+        assert_eq!(value(ParamType::Int32, "00000080"), Value::Int(i32::MIN));
+        assert_eq!(value(ParamType::Int32, "00000080").to_string(), "-2147483648");
+        assert!(matches!(value(ParamType::Int16, "ff"), Value::Invalid(_)));
+
+        /* Tround - Treply of TEST_SS_TWR_NTF */
+        let msg = decode_hex("6d08000500f6ffffff");
+        assert_eq!(msg.get("MEASUREMENT"), Some(&Value::Int(-10)));
+    }
+
+    /* the fields of `pkt`, checking that its typed payload is read from them too */
+    fn message_in(pkt: &Packet, ctx: &Context) -> Message {
+        decode_in(pkt, ctx).unwrap_or_else(|e| panic!("{}: {}", pkt, e));
+        collect_in(pkt, ctx).unwrap().1
+    }
+
+    fn decode_hex(s: &str) -> Message {
+        message_in(&Packet::from_hex(s).unwrap(), &Context::default())
+    }
+
+    fn decode_hex_with(s: &str, ctx: &Context) -> Message {
+        message_in(&Packet::from_hex(s).unwrap(), ctx)
+    }
+
+    #[test]
+    fn decode_variant_and_fields() {
+        let bytes = [0x60, 0x01, 0x00, 0x01, 0x01];
+        let msg = decode(&bytes).unwrap();
+        assert_eq!(msg, UciMessage::CoreDeviceStatusNtf(CoreDeviceStatusNtf { state: DeviceState::Ready }));
+        let msg = message_in(&Packet::from_bytes(&bytes).unwrap(), &Context::default());
+        assert_eq!((msg.mt, msg.gid, msg.oid, msg.name), (mt::NTF, gid::CORE, oid::CORE_DEVICE_STATUS, "CORE_DEVICE_STATUS_NTF"));
+        assert_eq!(msg.params.len(), 1);
+        assert_eq!(msg.params[0].offset, Some(0));
+        assert_eq!(msg.params[0].raw, vec![0x01]);
+        assert_eq!(msg.params[0].value.as_u32(), Some(1));
+
+        /* vendor messages keep the generic Message */
+        let msg = decode(&[0x4e, 0x00, 0x00, 0x01, 0x00]).unwrap();
+        let UciMessage::NxpCoreDeviceInitRsp(msg) = msg else { panic!("{:?}", msg) };
+        assert_eq!(msg.get("STATUS"), Some(&Value::Enum(0, Some("OK"))));
+    }
+
+    #[test]
+    fn decode_config_by_name() {
+        /* SESSION_ID 1, CHANNEL_NUMBER 9, RANGING_INTERVAL 200 */
+        let msg = decode(Packet::from_hex("2103000e01000000020401090904c8000000").unwrap().bytes()).unwrap();
+        let UciMessage::SessionSetAppConfigCmd(cmd) = msg else { panic!("{:?}", msg) };
+        assert_eq!(cmd.session_id, 1);
+        assert_eq!(cmd.config.params.len(), 2);
+        let channel = cmd.config.param("CHANNEL_NUMBER").unwrap();
+        assert_eq!(channel.name, "CHANNEL_NUMBER(0x04)");
+        assert_eq!(channel.value.as_u32(), Some(9));
+        assert_eq!(cmd.config.get("RANGING_INTERVAL").and_then(Value::as_u32), Some(200));
+        assert_eq!(cmd.config.get("CHANNEL"), None);
+    }
+
+    #[test]
+    fn decode_errors() {
+        assert!(decode(&[0x60, 0x01]).is_err());
+        assert!(decode(&[0x60, 0x01, 0x00, 0x02, 0x01]).unwrap_err().to_string().contains("payload length mismatch"));
+        assert!(decode(&[0x60, 0x3f, 0x00, 0x00]).unwrap_err().to_string().contains("unrecognized packet"));
+        assert!(decode(&[0x71, 0x03, 0x00, 0x01, 0x00]).unwrap_err().to_string().contains("segmented packet"));
+        /* a decoder error is returned, not a partial message */
+        assert!(decode(&[0x40, 0x01, 0x00, 0x00]).is_err());
+    }
+
+    /* NXP_SET_CALIBRATION_CMD on channel 9 */
+    fn set_calibration(id: u8, value: &str) -> Message {
+        let payload = format!("09{:02x}{}", id, value);
+        decode_hex(&format!("2e1100{:02x}{}", payload.len() / 2, payload))
+    }
+
+    /* the entries of calibration parameter `name` */
+    #[track_caller]
+    fn entries(msg: &Message, name: &str) -> Vec<Vec<(String, Value)>> {
+        assert_eq!(msg.get(name).and_then(Value::as_u32), Some(msg.get("Entries").and_then(Value::as_list).unwrap().len() as u32));
+        msg.get("Entries").and_then(Value::as_list).unwrap().iter()
+            .map(|e| e.iter().map(|p| (p.name.clone(), p.value.clone())).collect())
+            .collect()
+    }
+
+    fn entry(fields: &[(&str, Value)]) -> Vec<(String, Value)> {
+        fields.iter().map(|(n, v)| (n.to_string(), v.clone())).collect()
+    }
+
+    #[test]
+    fn calibration_per_antenna() {
+        let msg = set_calibration(0x0f, "0201aabb02ccdd");
+        assert_eq!(entries(&msg, "RX_ANT_DELAY_CALIB"), vec![
+            entry(&[("RX_ANTENNA_ID", Value::Hex(1)), ("DELAY", Value::Dec(0xbbaa))]),
+            entry(&[("RX_ANTENNA_ID", Value::Hex(2)), ("DELAY", Value::Dec(0xddcc))]),
+        ]);
+
+        let msg = set_calibration(0x10, "01030008");
+        assert_eq!(entries(&msg, "PDOA_OFFSET_CALIB"), vec![
+            entry(&[("RX_ANTENNA_PAIR_ID", Value::Hex(3)), ("PDOA_OFFSET", Value::Fixed(1.0, None, 0x800))]),
+        ]);
+
+        for (id, name) in [(0x13, "RSSI_CALIB_CONSTANT_HIGH_PWR"), (0x14, "RSSI_CALIB_CONSTANT_LOW_PWR")] {
+            let msg = set_calibration(id, "0101feff0200");
+            assert_eq!(entries(&msg, name), vec![
+                entry(&[("RX_ANTENNA_ID", Value::Hex(1)), ("RSSI_CALIB_CONSTANT1", Value::Int(-2)), ("RSSI_CALIB_CONSTANT2", Value::Int(2))]),
+            ]);
+        }
+
+        let msg = set_calibration(0x15, "0201ffff020100");
+        assert_eq!(entries(&msg, "SNR_CALIB_CONSTANT_PER_ANTENNA"), vec![
+            entry(&[("RX_ANTENNA_ID", Value::Hex(1)), ("SNR_CALIB_CONSTANT", Value::Int(-1))]),
+            entry(&[("RX_ANTENNA_ID", Value::Hex(2)), ("SNR_CALIB_CONSTANT", Value::Int(1))]),
+        ]);
+
+        let msg = set_calibration(0x17, "0201800104000200800004");
+        assert_eq!(entries(&msg, "TX_POWER_PER_ANTENNA"), vec![
+            entry(&[("TX_ANTENNA_ID", Value::Hex(1)), ("TX_POWER_DELTA_PEAK", Value::Fixed(1.5, None, 0x180)), ("TX_POWER_ID_RMS", Value::Int(4))]),
+            entry(&[("TX_ANTENNA_ID", Value::Hex(2)), ("TX_POWER_DELTA_PEAK", Value::Fixed(-128.0, None, 0x8000)), ("TX_POWER_ID_RMS", Value::Int(1024))]),
+        ]);
+
+        let msg = set_calibration(0x18, "0101fb0001");
+        assert_eq!(entries(&msg, "TX_TEMPERATURE_COMP_PER_ANTENNA"), vec![
+            entry(&[("TX_ANTENNA_ID", Value::Hex(1)), ("TEMPERATURE", Value::Int(-5)), ("TX_POWER_COMPENSATION", Value::Fixed(1.0, None, 0x100))]),
+        ]);
+
+        /* the PDoA values take the rest of each entry */
+        for id in [0x0b, 0x0c] {
+            let msg = set_calibration(id, "0201aabbcc02ddeeff");
+            assert_eq!(entries(&msg, "AOA_MULTIPOINT_PDOA_CALIB"), vec![
+                entry(&[("RX_ANTENNA_PAIR_ID", Value::Hex(1)), ("PDOA_VALUES", Value::Bytes(vec![0xaa, 0xbb, 0xcc]))]),
+                entry(&[("RX_ANTENNA_PAIR_ID", Value::Hex(2)), ("PDOA_VALUES", Value::Bytes(vec![0xdd, 0xee, 0xff]))]),
+            ]);
+        }
+
+        /* no entries */
+        let msg = set_calibration(0x0f, "00");
+        assert!(entries(&msg, "RX_ANT_DELAY_CALIB").is_empty());
+    }
+
+    #[test]
+    fn calibration_malformed() {
+        let invalid = |id: u8, value: &str, name: &str| {
+            let msg = set_calibration(id, value);
+            assert!(matches!(msg.get(name), Some(Value::Invalid(_))), "{} {}: {:?}", name, value, msg.get(name));
+            assert_eq!(msg.get("Entries"), None);
+        };
+        /* entries larger than the layout */
+        invalid(0x0f, "0201aabbcc02ddeeff", "RX_ANT_DELAY_CALIB");
+        /* entries smaller than the layout */
+        invalid(0x17, "01010203", "TX_POWER_PER_ANTENNA");
+        /* count does not divide the payload */
+        invalid(0x15, "0201ffff0201", "SNR_CALIB_CONSTANT_PER_ANTENNA");
+        invalid(0x0b, "0201aabb02dd", "AOA_MULTIPOINT_PDOA_CALIB");
+        /* entries announced but missing */
+        invalid(0x13, "02", "RSSI_CALIB_CONSTANT_HIGH_PWR");
+    }
+
+    #[test]
+    fn get_calibration_uses_the_request() {
+        let mut ctx = Context::default();
+        ctx.update(&Packet::from_hex("2e1200020910").unwrap());
+        let collector = Collector::new();
+        print_packet(&collector, &Packet::from_hex("4e1200050001010008").unwrap(), &ctx).unwrap();
+        let (params, _) = collector.into_parts();
+        assert_eq!(params[1].name, "PDOA_OFFSET_CALIB(0x10)");
+        assert_eq!(params[1].value, Value::Dec(1));
+    }
+
+    fn device_info_rsp(status: u8, major: u8) -> Packet {
+        Packet::from_bytes(&[0x40, 0x02, 0x00, 0x0a, status, major, 0x00, 0x01, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00]).unwrap()
+    }
+
+    #[test]
+    fn device_info() {
+        let mut ctx = Context::default();
+        /* UCI 2.0.0, MAC 1.3.0, PHY 1.3.0, test 1.1.0; NXP DEVICE_NAME and FIRMWARE_VERSION */
+        let pkt = Packet::from_hex("400200190002000130013001100f02e300055352313530e30103010203").unwrap();
+        let msg = message_in(&pkt, &ctx);
+        assert_eq!(msg.get("STATUS"), Some(&Value::Enum(0, Some("OK"))));
+        assert_eq!(msg.get("UCI_GENERIC_VERSION"), Some(&Value::Text("2.0.0".to_string())));
+        assert_eq!(msg.get("MAC_VERSION"), Some(&Value::Text("1.3.0".to_string())));
+        assert_eq!(msg.get("PHY_VERSION"), Some(&Value::Text("1.3.0".to_string())));
+        assert_eq!(msg.get("UCI_TEST_VERSION"), Some(&Value::Text("1.1.0".to_string())));
+        assert_eq!(msg.get("VENDOR_SPECIFIC_INFO_LENGTH"), Some(&Value::Dec(15)));
+        assert!(msg.comments.iter().any(|c| c == "NXP vendor info"));
+        assert_eq!(msg.get("DEVICE_NAME"), Some(&Value::Text("SR150".to_string())));
+        assert_eq!(msg.get("FIRMWARE_VERSION"), Some(&Value::Text("01.02.03".to_string())));
+        let UciMessage::CoreGetDeviceInfoRsp(rsp) = decode_in(&pkt, &ctx).unwrap() else { panic!() };
+        assert_eq!(rsp.status, Code { value: 0, name: Some("OK") });
+        assert_eq!(rsp.uci_version, Version { major: 2, minor: 0, maintenance: 0 });
+        assert_eq!(rsp.mac_version, Version { major: 1, minor: 3, maintenance: 0 });
+        assert_eq!(rsp.uci_test_version, Version { major: 1, minor: 1, maintenance: 0 });
+        assert_eq!(rsp.vendor_info.len(), 15);
+        assert_eq!(rsp.vendor_config.get("DEVICE_NAME"), Some(&Value::Text("SR150".to_string())));
+        ctx.update(&pkt);
+        assert_eq!(ctx.version, UciVersion::V2);
+        assert!(ctx.nxp);
+
+        /* other vendors' info is shown raw */
+        let mut ctx = Context::default();
+        let pkt = Packet::from_hex("4002000c00011001100110011002aabb").unwrap();
+        let msg = message_in(&pkt, &ctx);
+        assert_eq!(msg.get("UCI_GENERIC_VERSION"), Some(&Value::Text("1.1.0".to_string())));
+        assert_eq!(msg.get("VENDOR_SPECIFIC_INFO"), Some(&Value::Bytes(vec![0xaa, 0xbb])));
+        assert!(msg.comments.is_empty());
+        ctx.update(&pkt);
+        assert_eq!(ctx.version, UciVersion::V1);
+        assert!(!ctx.nxp);
+
+        let err = decode_in(&Packet::from_hex("4002000c00011001100110011003aabb").unwrap(), &ctx).unwrap_err();
+        assert_eq!(err.to_string(), "vendor info length mismatch: expected=3 actual=2");
+    }
+
+    #[test]
+    fn caps_info_bitmasks() {
+        /* DEVICE_ROLES over two bytes, HOPPING_MODE with a bit it does not name */
+        let msg = decode_hex("40030009000202028301080105");
+        assert_eq!(msg.get("Number of parameters"), Some(&Value::Dec(2)));
+        let roles = msg.param("DEVICE_ROLES").unwrap();
+        assert_eq!(roles.name, "DEVICE_ROLES(0x02)");
+        assert_eq!(roles.value, Value::Flags(0x183, vec![
+            "Responder".to_string(), "Initiator".to_string(), "DT-Anchor".to_string(), "DT-Tag".to_string(),
+        ]));
+        assert_eq!(roles.value.to_string(), "0x183 (Responder | Initiator | DT-Anchor | DT-Tag)");
+        assert_eq!(msg.get("HOPPING_MODE"), Some(&Value::Flags(5, vec!["Hopping".to_string(), "bit 2".to_string()])));
+
+        /* a failed request carries the status alone */
+        let msg = decode_hex("4003000101");
+        assert_eq!(msg.params.len(), 1);
+        assert_eq!(msg.get("STATUS"), Some(&Value::Enum(1, Some("REJECTED"))));
+    }
+
+    /* values of all fields named `name`, in order */
+    fn all(params: &[Param], name: &str) -> Vec<Value> {
+        params.iter().filter(|p| p.name == name).map(|p| p.value.clone()).collect()
+    }
+
+    #[test]
+    fn get_config() {
+        let msg = decode_hex("200500060301e404e47f");
+        assert_eq!(all(&msg.params, "PARAMETER_ID"), vec![
+            Value::Enum(1, Some("LOW_POWER_MODE")),
+            Value::Text("0xe4:0x04 (DPD_ENTRY_TIMEOUT)".to_string()),
+            Value::Text("0xe4:0x7f (Unknown)".to_string()),
+        ]);
+        let err = decode_packet(&Packet::from_hex("200500020201").unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "length mismatch");
+
+        let msg = decode_hex("4005000a0002010101e40402f401");
+        assert_eq!(msg.get("STATUS"), Some(&Value::Enum(0, Some("OK"))));
+        assert_eq!(msg.get("LOW_POWER_MODE"), Some(&Value::Hex(1)));
+        assert_eq!(msg.get("DPD_ENTRY_TIMEOUT"), Some(&Value::Dec(500)));
+        let msg = decode_hex("4005000101");
+        assert_eq!(msg.params.len(), 1);
+    }
+
+    #[test]
+    fn get_app_config() {
+        let msg = decode_hex("210400070100000002047e");
+        assert_eq!(msg.get("SESSION_ID"), Some(&Value::Hex(1)));
+        assert_eq!(all(&msg.params, "PARAMETER_ID"), vec![Value::Enum(4, Some("CHANNEL_NUMBER")), Value::Enum(0x7e, None)]);
+
+        let msg = decode_hex("410400050001040109");
+        assert_eq!(msg.get("CHANNEL_NUMBER"), Some(&Value::Hex(9)));
+        let msg = decode_hex("4104000101");
+        assert_eq!(msg.params.len(), 1);
+    }
+
+    #[test]
+    fn set_config_status() {
+        /* CORE_SET_CONFIG_RSP: LOW_POWER_MODE invalid, DPD_ENTRY_TIMEOUT rejected */
+        let msg = decode_hex("4004000704020104e40401");
+        assert_eq!(msg.get("STATUS"), Some(&Value::Enum(4, Some("INVALID_PARAM"))));
+        assert_eq!(msg.get("Number of parameters"), Some(&Value::Dec(2)));
+        let items = msg.get("Parameter status").and_then(Value::as_list).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(all(&items[0], "PARAMETER_ID"), vec![Value::Enum(1, Some("LOW_POWER_MODE"))]);
+        assert_eq!(all(&items[0], "STATUS"), vec![Value::Enum(4, Some("INVALID_PARAM"))]);
+        assert_eq!(all(&items[1], "PARAMETER_ID"), vec![Value::Text("0xe4:0x04 (DPD_ENTRY_TIMEOUT)".to_string())]);
+        assert_eq!(all(&items[1], "STATUS"), vec![Value::Enum(1, Some("REJECTED"))]);
+        assert_eq!(items[1][1].offset, Some(6));
+
+        /* SESSION_SET_APP_CONFIG_RSP */
+        let msg = decode_hex("4103000404010404");
+        let items = msg.get("Parameter status").and_then(Value::as_list).unwrap();
+        assert_eq!(all(&items[0], "PARAMETER_ID"), vec![Value::Enum(4, Some("CHANNEL_NUMBER"))]);
+        assert_eq!(all(&items[0], "STATUS"), vec![Value::Enum(4, Some("INVALID_PARAM"))]);
+
+        /* success carries the status alone */
+        let msg = decode_hex("4103000100");
+        assert_eq!(msg.params.len(), 1);
+        assert!(decode_packet(&Packet::from_hex("4103000404020404").unwrap()).is_err());
+    }
+
+    #[test]
+    fn generic_error_last_command() {
+        let error = Packet::from_hex("6007000104").unwrap();
+        let mut ctx = Context::default();
+        ctx.update(&Packet::from_hex("210000050100000000").unwrap());
+        let msg = message_in(&error, &ctx);
+        assert_eq!(msg.get("STATUS"), Some(&Value::Enum(4, Some("INVALID_PARAM"))));
+        assert_eq!(msg.comments, vec!["Likely refers to SESSION_INIT_CMD (not answered yet)"]);
+
+        /* once answered, the command is only mentioned */
+        ctx.update(&Packet::from_hex("4100000100").unwrap());
+        let msg = message_in(&error, &ctx);
+        assert_eq!(msg.comments, vec!["Last command: SESSION_INIT_CMD"]);
+
+        /* a response to another command does not answer it */
+        let mut ctx = Context::default();
+        ctx.update(&Packet::from_hex("20020000").unwrap());
+        ctx.update(&Packet::from_hex("4100000100").unwrap());
+        let msg = message_in(&error, &ctx);
+        assert_eq!(msg.comments, vec!["Likely refers to CORE_GET_DEVICE_INFO_CMD (not answered yet)"]);
+    }
+
+    #[test]
+    fn generic_error_without_command() {
+        let mut ctx = Context::default();
+        ctx.update(&Packet::from_hex("6001000101").unwrap());
+        let msg = message_in(&Packet::from_hex("6007000104").unwrap(), &ctx);
+        assert_eq!(msg.get("STATUS"), Some(&Value::Enum(4, Some("INVALID_PARAM"))));
+        assert!(msg.comments.is_empty());
+    }
+
+    /* SESSION_INIT_CMD of session 1, SESSION_INIT_RSP with handle 0x44332211, RANGE_START_CMD */
+    fn session_stream(ctx: &mut Context) -> Vec<Message> {
+        ["210000050100000000", "410000050011223344", "2200000411223344"].iter().map(|hex| {
+            let pkt = Packet::from_hex(hex).unwrap();
+            let msg = message_in(&pkt, ctx);
+            ctx.update(&pkt);
+            msg
+        }).collect()
+    }
+
+    #[test]
+    fn session_handle_v2() {
+        let mut ctx = Context::with_version(UciVersion::V2);
+        let msgs = session_stream(&mut ctx);
+        assert_eq!(msgs[1].get("SESSION_HANDLE"), Some(&Value::Hex(0x44332211)));
+        assert_eq!(msgs[1].get("ORIGINAL_SESSION_ID"), Some(&Value::Text("0x1".to_string())));
+        assert_eq!(msgs[2].get("SESSION_ID"), None);
+        assert_eq!(msgs[2].get("SESSION_HANDLE"), Some(&Value::Hex(0x44332211)));
+        assert_eq!(msgs[2].get("ORIGINAL_SESSION_ID"), Some(&Value::Text("0x1".to_string())));
+
+        /* TWR_SHORT is for session (handle) 0x44332211 */
+        let msg = message_in(&Packet::from_hex(TWR_SHORT).unwrap(), &ctx);
+        assert_eq!(msg.get("Session Handle"), Some(&Value::Hex(0x44332211)));
+        assert_eq!(msg.get("ORIGINAL_SESSION_ID"), Some(&Value::Text("0x1".to_string())));
+
+        /* other handles are not mapped */
+        let msg = decode_hex_with("2200000405000000", &ctx);
+        assert_eq!(msg.get("SESSION_HANDLE"), Some(&Value::Hex(5)));
+        assert_eq!(msg.get("ORIGINAL_SESSION_ID"), None);
+    }
+
+    #[test]
+    fn session_handle_v1() {
+        let mut ctx = Context::with_version(UciVersion::V1);
+        let msgs = session_stream(&mut ctx);
+        assert!(ctx.handles.is_empty());
+        assert_eq!(msgs[1].get("SESSION_HANDLE"), None);
+        assert_eq!(msgs[1].get("ORIGINAL_SESSION_ID"), None);
+        assert_eq!(msgs[2].get("SESSION_ID"), Some(&Value::Hex(0x44332211)));
+        assert_eq!(msgs[2].get("ORIGINAL_SESSION_ID"), None);
+        let msg = message_in(&Packet::from_hex(TWR_SHORT).unwrap(), &ctx);
+        assert_eq!(msg.get("Session ID"), Some(&Value::Hex(0x44332211)));
+        assert_eq!(msg.get("ORIGINAL_SESSION_ID"), None);
+    }
+
+    #[test]
+    fn version_detection() {
+        let mut ctx = Context::default();
+        assert_eq!(ctx.version, UciVersion::V1);
+        /* a failed response says nothing */
+        ctx.update(&device_info_rsp(0x01, 2));
+        assert_eq!(ctx.version, UciVersion::V1);
+        ctx.update(&device_info_rsp(0x00, 2));
+        assert_eq!(ctx.version, UciVersion::V2);
+        ctx.update(&device_info_rsp(0x00, 1));
+        assert_eq!(ctx.version, UciVersion::V1);
+
+        /* a version set by the user is kept */
+        let mut ctx = Context::with_version(UciVersion::V1);
+        ctx.update(&device_info_rsp(0x00, 2));
+        assert_eq!(ctx.version, UciVersion::V1);
+    }
+
+    #[test]
+    fn session_status_v2() {
+        let bytes = [0x61, 0x02, 0x00, 0x06, 0x01, 0x00, 0x00, 0x00, 0x02, 0x03];
+        let v1 = decode(&bytes).unwrap();
+        assert_eq!(v1, UciMessage::SessionStatusNtf(SessionStatusNtf {
+            session_id: 1,
+            state: SessionState::Active,
+            reason: Code { value: 3, name: None },
+        }));
+
+        let v2 = decode_with(&bytes, UciVersion::V2).unwrap();
+        assert_eq!(v2, UciMessage::SessionStatusNtf(SessionStatusNtf {
+            session_id: 1,
+            state: SessionState::Active,
+            reason: Code { value: 3, name: Some("SESSION_SUSPENDED_DUE_TO_INBAND_SIGNAL") },
+        }));
+        let v2 = message_in(&Packet::from_bytes(&bytes).unwrap(), &Context::with_version(UciVersion::V2));
+        assert_eq!(v2.get("SESSION_ID"), None);
+        assert_eq!(v2.get("SESSION_HANDLE"), Some(&Value::Hex(1)));
+    }
+
+    #[test]
+    fn multicast_list_ntf() {
+        /* UCI 1.x: remaining size, count, then MAC address, sub-session ID and status per controlee */
+        let bytes = [0x61, 0x07, 0x00, 0x0d, 0x01, 0x00, 0x00, 0x00, 0x05, 0x01, 0x34, 0x12, 0x78, 0x56, 0x34, 0x12, 0x00];
+        let v1 = message_in(&Packet::from_bytes(&bytes).unwrap(), &Context::default());
+        let controlees = v1.get("Controlees").and_then(Value::as_list).unwrap();
+        assert_eq!(controlees[0].iter().map(|p| p.name.as_str()).collect::<Vec<&str>>(), ["MAC_ADDRESS", "SUB_SESSION_ID", "STATUS"]);
+        assert_eq!(decode(&bytes).unwrap(), UciMessage::SessionUpdateControllerMulticastListNtf(SessionUpdateControllerMulticastListNtf {
+            session_id: 1,
+            remaining_multicast_list_size: Some(5),
+            controlees: vec![ControleeStatus {
+                mac_address: 0x1234,
+                sub_session_id: Some(0x12345678),
+                status: Code { value: 0, name: Some("OK_MULTICAST_LIST_UPDATE") },
+            }],
+        }));
+
+        /* UCI 2.0: count, then MAC address and status per controlee */
+        let v2 = decode_with(&[0x61, 0x07, 0x00, 0x0b, 0x01, 0x00, 0x00, 0x00, 0x02, 0x34, 0x12, 0x00, 0x78, 0x56, 0x01], UciVersion::V2).unwrap();
+        let UciMessage::SessionUpdateControllerMulticastListNtf(v2) = v2 else { panic!("{:?}", v2) };
+        assert_eq!((v2.session_id, v2.remaining_multicast_list_size), (1, None));
+        assert_eq!(v2.controlees.len(), 2);
+        assert_eq!(v2.controlees[1], ControleeStatus {
+            mac_address: 0x5678,
+            sub_session_id: None,
+            status: Code { value: 1, name: Some("ERROR_MULTICAST_LIST_FULL") },
+        });
+    }
+
+    /* DATA_MESSAGE_SND with `app_len` announced and `data` bytes of application data */
+    fn data_message_snd(app_len: u16, data: usize) -> Vec<u8> {
+        let mut payload = vec![0x44, 0x33, 0x22, 0x11, 1, 2, 3, 4, 5, 6, 7, 8, 0x05, 0x00];
+        payload.extend_from_slice(&app_len.to_le_bytes());
+        payload.extend(std::iter::repeat_n(0xa5, data));
+        let mut bytes = vec![0x01, 0x00];
+        bytes.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        bytes.extend(payload);
+        bytes
+    }
+
+    #[test]
+    fn data_message_long() {
+        /* data packets are decoded to the generic Message */
+        let msg = decode(&data_message_snd(300, 300)).unwrap();
+        let UciMessage::DataMessageSnd(msg) = msg else { panic!("{:?}", msg) };
+        assert_eq!((msg.mt, msg.gid, msg.oid), (mt::DATA, dpf::DATA_MESSAGE_SND, 0));
+        assert_eq!(msg.get("SESSION_HANDLE"), Some(&Value::Hex(0x11223344)));
+        assert_eq!(msg.get("SEQUENCE_NUMBER"), Some(&Value::Dec(5)));
+        assert_eq!(msg.get("APPLICATION_DATA_LENGTH"), Some(&Value::Dec(300)));
+        assert_eq!(msg.get("APPLICATION_DATA"), Some(&Value::Bytes(vec![0xa5; 300])));
+
+        let mut rcv = vec![0x02, 0x00, 0x2b, 0x01, 0x44, 0x33, 0x22, 0x11, 0x00, 1, 2, 3, 4, 5, 6, 7, 8, 0x06, 0x00, 0x1a, 0x01];
+        rcv.extend(std::iter::repeat_n(0x5a, 282));
+        let msg = decode(&rcv).unwrap();
+        let UciMessage::DataMessageRcv(msg) = msg else { panic!("{:?}", msg) };
+        assert_eq!(msg.get("APPLICATION_DATA"), Some(&Value::Bytes(vec![0x5a; 282])));
+    }
+
+    #[test]
+    fn data_message_length_mismatch() {
+        /* the 16-bit header length must cover the payload */
+        let mut bytes = data_message_snd(300, 300);
+        bytes.pop();
+        assert!(decode(&bytes).unwrap_err().to_string().contains("payload length mismatch"));
+
+        /* APPLICATION_DATA_LENGTH must cover the application data */
+        let err = decode(&data_message_snd(300, 299)).unwrap_err();
+        assert!(err.to_string().contains("application data length mismatch"), "{}", err);
+    }
+
+    fn report_params(msg: &Message) -> &Vec<Vec<Param>> {
+        match msg.get("Reports") {
+            Some(Value::List(items)) => items,
+            v => panic!("no reports: {:?}", v),
+        }
+    }
+
+    /*
+     * TWR RANGE_DATA_NTF, short addresses, one measurement. The MAC addressing
+     * mode is byte 15 (byte 16 is RFU) and the measurements start after the
+     * count at byte 24; a short address is 2 bytes and each measurement is
+     * padded to 31 bytes.
+     */
+    const TWR_SHORT: &str = concat!(
+        "62000038",
+        "01000000", "11223344", "00", "c8000000", "01", "00", "00", "0000000000000000", "01",
+        "3412", "00", "01", "6400", "0000", "00", "0000", "00", "0000", "00", "0000", "00", "07",
+        "000000000000000000000000",
+    );
+
+    #[test]
+    fn range_data_twr_short() {
+        let msg = decode_hex(TWR_SHORT);
+        assert_eq!(msg.get("Mac addressing mode"), Some(&Value::Enum(0, Some("short"))));
+        let reports = report_params(&msg);
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        let mac = report.iter().find(|p| p.name == "Mac Address").unwrap();
+        assert_eq!((mac.offset, &mac.value), (Some(25), &Value::Hex(0x1234)));
+        assert_eq!(report.iter().find(|p| p.name == "Distance").map(|p| &p.value), Some(&Value::Dec(100)));
+        let slot = report.iter().find(|p| p.name == "Slot Index").unwrap();
+        assert_eq!((slot.offset, &slot.value), (Some(43), &Value::Dec(7)));
+
+        let msg = decode(Packet::from_hex(TWR_SHORT).unwrap().bytes()).unwrap();
+        let UciMessage::RangeDataNtf(ntf) = msg else { panic!("{:?}", msg) };
+        assert_eq!((ntf.sequence_number, ntf.session_id, ntf.ranging_interval), (1, 0x44332211, 200));
+        assert_eq!(ntf.rcr_indication, None);
+        assert_eq!(ntf.mac_addressing_mode, Code { value: 0, name: Some("short") });
+        assert!(ntf.vendor.is_empty());
+        let [Measurement::Twr(twr)] = &ntf.measurements[..] else { panic!("{:?}", ntf.measurements) };
+        assert_eq!(twr.mac_address, MacAddress::Short(0x1234));
+        assert_eq!(twr.status.value, 0);
+        assert_eq!(twr.nlos.value, 1);
+        assert_eq!(twr.distance, 100);
+        assert_eq!(twr.aoa_azimuth, Aoa { angle: 0.0, fom: 0 });
+        assert_eq!(twr.slot_index, 7);
+        assert_eq!(twr.rssi, None);
+    }
+
+    #[test]
+    fn range_data_json() {
+        let info = FrameInfo { timestamp: Some("01-02 03:04:05.678".to_string()), direction: Some(Direction::UwbsToHost) };
+        let line = json_packet(&Packet::from_hex(TWR_SHORT).unwrap(), &info, &Context::default());
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["timestamp"], "01-02 03:04:05.678");
+        assert_eq!((json["mt"].as_u64(), json["gid"].as_u64(), json["oid"].as_u64()), (Some(3), Some(2), Some(0)));
+        assert_eq!(json["name"], "RANGE_DATA_NTF");
+        assert!(json.get("error").is_none());
+
+        let fields = json["fields"].as_array().unwrap();
+        assert_eq!(fields[0]["name"], "Sequence number");
+        assert_eq!((fields[0]["offset"].as_u64(), &fields[0]["raw"], &fields[0]["value"]), (Some(0), &"01000000".into(), &"1".into()));
+        let reports = fields.iter().find(|f| f["name"] == "Reports").unwrap()["items"].as_array().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0]["title"], "Report 0");
+        let mac = &reports[0]["fields"][0];
+        assert_eq!((&mac["name"], mac["offset"].as_u64(), &mac["raw"]), (&"Mac Address".into(), Some(25), &"3412".into()));
+    }
+
+    #[test]
+    fn json_error() {
+        let line = json_packet(&Packet::from_hex("62000001ff").unwrap(), &FrameInfo::default(), &Context::default());
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(json["name"], "RANGE_DATA_NTF");
+        assert_eq!(json["error"], "mismatch length");
+        assert_eq!(json["fields"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn range_data_twr_long() {
+        let msg = decode_hex(concat!(
+            "62000038",
+            "02000000", "11223344", "00", "c8000000", "01", "00", "01", "0000000000000000", "01",
+            "0807060504030201", "00", "00", "2c01", "0000", "00", "0000", "00", "0000", "00", "0000", "00", "00",
+            "000000000000",
+        ));
+        assert_eq!(msg.get("Mac addressing mode"), Some(&Value::Enum(1, Some("long"))));
+        let report = &report_params(&msg)[0];
+        assert_eq!(report[0].value, Value::Bytes(vec![8, 7, 6, 5, 4, 3, 2, 1]));
+        assert_eq!(report.iter().find(|p| p.name == "Distance").map(|p| &p.value), Some(&Value::Dec(300)));
+    }
+
+    /* TWR_SHORT followed by `tail` */
+    fn twr_short_with(tail: &str) -> Packet {
+        let payload = &TWR_SHORT[8..];
+        Packet::from_hex(&format!("620000{:02x}{}{}", (payload.len() + tail.len()) / 2, payload, tail)).unwrap()
+    }
+
+    fn nxp_context() -> Context {
+        let mut ctx = Context::default();
+        /* CORE_SET_CONFIG_CMD: NXP_EXTENDED_NTF_CONFIG = 1 */
+        ctx.update(&Packet::from_hex("2004000501e4330101").unwrap());
+        assert!(ctx.nxp);
+        ctx
+    }
+
+    #[test]
+    fn nxp_detection() {
+        let mut ctx = Context::default();
+        ctx.update(&device_info_rsp(0x00, 2));
+        assert!(!ctx.nxp);
+        /* vendor info: FIRMWARE_VERSION as an extended TLV */
+        ctx.update(&Packet::from_hex("400200110000010001000100010701e30103010203").unwrap());
+        assert!(ctx.nxp);
+
+        let mut ctx = Context::default();
+        ctx.update(&Packet::from_hex("2004000501e4340101").unwrap());
+        assert!(!ctx.nxp);
+        /* CORE_GET_CONFIG_RSP */
+        ctx.update(&Packet::from_hex("400500060001e4330101").unwrap());
+        assert!(ctx.nxp);
+    }
+
+    #[test]
+    fn nxp_extended_ntf_disabled() {
+        let mut ctx = Context::default();
+        ctx.update(&Packet::from_hex("2004000501e4330100").unwrap());
+        assert!(!ctx.nxp);
+        ctx.update(&Packet::from_hex("400500060001e4330100").unwrap());
+        assert!(!ctx.nxp);
+
+        /* trailing bytes of a range data notification stay undecoded */
+        let pkt = twr_short_with("0a01010001000200aabb01");
+        let msg = message_in(&pkt, &ctx);
+        assert_eq!(msg.get("Vendor specific length"), None);
+        assert_eq!(msg.get("RSSI per antenna"), None);
+        assert_eq!(msg.get("Unknown").and_then(Value::as_bytes), Some(&pkt.payload()[56..]));
+    }
+
+    #[test]
+    fn range_data_nxp_ext() {
+        /* one RX antenna, no antenna pairs, 2 bytes of CIR, no conflict */
+        let pkt = twr_short_with("0a01010001000200aabb01");
+        let msg = message_in(&pkt, &nxp_context());
+        assert_eq!(msg.get("Vendor specific length"), Some(&Value::Dec(10)));
+        assert_eq!(msg.get("Number of RX antennas"), Some(&Value::Dec(1)));
+        assert_eq!(msg.get("CIR length"), Some(&Value::Dec(2)));
+        assert_eq!(msg.get("CIR").and_then(Value::as_bytes), Some(&[0xaa, 0xbb][..]));
+        assert_eq!(msg.get("WiFi coex status"), Some(&Value::Enum(1, Some("No conflict"))));
+        assert_eq!(msg.get("Unknown"), None);
+
+        /* other vendors' data is left alone */
+        let msg = message_in(&pkt, &Context::default());
+        assert_eq!(msg.get("Vendor specific length"), None);
+        assert_eq!(msg.get("Unknown").and_then(Value::as_bytes), Some(&pkt.payload()[56..]));
+    }
+
+    #[test]
+    fn range_data_nxp_ext_cir_too_long() {
+        for cir in ["ffff", "0080", "0300"] {
+            let pkt = twr_short_with(&format!("060000{}aabb", cir));
+            let err = decode_in(&pkt, &nxp_context()).unwrap_err();
+            assert!(err.to_string().contains("CIR length mismatch"), "{}", err);
+        }
+        /* no room for the coex status */
+        let err = decode_in(&twr_short_with("0600000200aabb"), &nxp_context()).unwrap_err();
+        assert_eq!(err.to_string(), "length mismatch");
+        let err = decode_in(&twr_short_with("040000ffff"), &nxp_context()).unwrap_err();
+        assert!(err.to_string().contains("CIR length mismatch"), "{}", err);
+    }
+
+    #[test]
+    fn cir_capture_ntf() {
+        let msg = decode_hex("6b01000a01000000020300aabbcc");
+        assert_eq!(msg.get("SESSION_ID"), Some(&Value::Hex(1)));
+        assert_eq!(msg.get("RX_ANTENNA_ID"), Some(&Value::Hex(2)));
+        assert_eq!(msg.get("CIR_LEN"), Some(&Value::Dec(3)));
+        assert_eq!(msg.get("CIR_DATA").and_then(Value::as_bytes), Some(&[0xaa, 0xbb, 0xcc][..]));
+
+        /* CIR_LEN past the end of the payload, including lengths above 0x7fff */
+        for (hex, len) in [("6b0100070100000000ffff", 65535), ("6b01000701000000000080", 32768), ("6b01000901000000000300aabb", 3)] {
+            let err = decode_packet(&Packet::from_hex(hex).unwrap()).unwrap_err();
+            let avail = hex.len() / 2 - 11;
+            assert_eq!(err.to_string(), format!("CIR_DATA length mismatch: expected={} actual={}", len, avail));
+        }
+    }
+
+    #[test]
+    fn ccc_session() {
+        let mut ctx = Context::default();
+        /* SESSION_INIT_CMD: session 1 of type CCC */
+        ctx.update(&Packet::from_hex("2100000501000000a0").unwrap());
+
+        /* SESSION_SET_APP_CONFIG_CMD: HOP_MODE_KEY and URSK_TTL, then CHANNEL_NUMBER */
+        let set_config = |session: &str| Packet::from_hex(&format!("21030012{}03a00444332211a6021027040109", session)).unwrap();
+        let msg = message_in(&set_config("01000000"), &ctx);
+        assert_eq!(msg.param("HOP_MODE_KEY").map(|p| p.name.as_str()), Some("HOP_MODE_KEY(0xa0)"));
+        assert_eq!(msg.get("HOP_MODE_KEY"), Some(&Value::Hex(0x11223344)));
+        assert_eq!(msg.get("URSK_TTL"), Some(&Value::Dec(10000)));
+        assert_eq!(msg.get("CHANNEL_NUMBER").and_then(Value::as_u32), Some(9));
+
+        /* other sessions do not know the CCC parameters */
+        let msg = message_in(&set_config("02000000"), &ctx);
+        assert_eq!(msg.get("HOP_MODE_KEY"), None);
+        assert_eq!(msg.get("Unknown(0xa0 0x04)"), Some(&Value::Bytes(vec![0x44, 0x33, 0x22, 0x11])));
+        assert_eq!(msg.get("CHANNEL_NUMBER").and_then(Value::as_u32), Some(9));
+
+        /* RANGE_CCC_DATA_NTF of session 1 */
+        let pkt = Packet::from_hex(concat!(
+            "6220001d",
+            "01000000", "00", "78563412", "0500", "6400", "01", "02", "0102030405060708", "c0f9", "05", "0000", "00",
+        )).unwrap();
+        let msg = message_in(&pkt, &ctx);
+        assert_eq!(msg.name, "RANGE_CCC_DATA_NTF");
+        assert_eq!(msg.get("SESSION_ID"), Some(&Value::Hex(1)));
+        assert_eq!(msg.get("RANGING_STATUS"), Some(&Value::Enum(0, Some("OK"))));
+        assert_eq!(msg.get("STS_INDEX"), Some(&Value::Hex(0x12345678)));
+        assert_eq!(msg.get("RR_INDEX"), Some(&Value::Dec(5)));
+        assert_eq!(msg.get("DISTANCE"), Some(&Value::Dec(100)));
+        assert_eq!(msg.get("UNCERTAINTY_INITIATOR_FOM"), Some(&Value::Dec(2)));
+        assert_eq!(msg.get("CCM_TAG").and_then(Value::as_bytes), Some(&[1, 2, 3, 4, 5, 6, 7, 8][..]));
+        assert_eq!(msg.get("AOA_AZIMUTH").and_then(Value::as_f64), Some(-12.5));
+        assert_eq!(msg.get("AOA_AZIMUTH_FOM"), Some(&Value::Dec(5)));
+        let last = msg.params.last().unwrap();
+        assert_eq!((last.name.as_str(), last.offset), ("AOA_ELEVATION_FOM", Some(28)));
+        assert!(decode_in(&Packet::from_hex("6220000401000000").unwrap(), &ctx).is_err());
+
+        /* RANGE_DATA_NTF keeps the FiRa layout in CCC sessions */
+        let pkt = range_data_ntf(1, 0, &[TWR_SHORT[58..].to_string()]);
+        let msg = message_in(&pkt, &ctx);
+        assert_eq!(msg, message_in(&pkt, &Context::default()));
+        assert!(field(&report_params(&msg)[0], "NLoS").is_some());
+    }
+
+    /* RANGE_DATA_NTF of session 1 carrying `reports` */
+    fn range_data_ntf(ranging_type: u8, mac_mode: u8, reports: &[String]) -> Packet {
+        let payload = format!("010000000100000000c8000000{:02x}00{:02x}0000000000000000{:02x}{}",
+                              ranging_type, mac_mode, reports.len(), reports.concat());
+        Packet::from_hex(&format!("620000{:02x}{}", payload.len() / 2, payload)).unwrap()
+    }
+
+    /* the reports, checking that the last one ends the payload */
+    fn reports_to_end(pkt: &Packet) -> Vec<Vec<Param>> {
+        let msg = message_in(pkt, &Context::default());
+        assert_eq!(msg.get("Unknown"), None);
+        let reports = report_params(&msg).clone();
+        let last = reports.last().unwrap().last().unwrap();
+        assert_eq!(last.offset.unwrap() + last.raw.len(), pkt.len());
+        reports
+    }
+
+    fn field<'a>(report: &'a [Param], name: &str) -> Option<&'a Param> {
+        report.iter().find(|p| p.name == name)
+    }
+
+    #[test]
+    fn range_data_tdoa() {
+        /* RX timestamp, device ID and TX timestamp sizes selected by the message control */
+        for (control, rx, device, tx) in [
+            (0x00u8, 5, 0, 0), (0x01, 8, 0, 0),
+            (0x02, 5, 2, 0), (0x04, 5, 4, 0), (0x06, 5, 8, 0),
+            (0x08, 5, 0, 5), (0x10, 5, 0, 8), (0x18, 5, 0, 0),
+            (0x1f, 8, 8, 0), (0x0b, 8, 2, 5), (0x15, 8, 4, 8),
+        ] {
+            for (mac_mode, mac) in [(0u8, "3412"), (1, "0807060504030201")] {
+                let report = format!("{}00{:02x}0100c0ff000000050a000000{}{}{}", mac, control,
+                                     "a1".repeat(rx), "d1".repeat(device), "c1".repeat(tx));
+                let pkt = range_data_ntf(0, mac_mode, &[report.clone(), report.clone()]);
+                let reports = reports_to_end(&pkt);
+                let size = report.len() / 2;
+                assert_eq!(size, mac.len() / 2 + 14 + rx + device + tx);
+
+                for (i, report) in reports.iter().enumerate() {
+                    let start = 25 + i * size;
+                    assert_eq!(report[0].offset, Some(start), "control {:#x}", control);
+                    assert_eq!(field(report, "Message Control").unwrap().value, Value::Hex(control.into()));
+                    assert_eq!(field(report, "Frame Type").unwrap().value, Value::Enum(1, Some("Synchronization UTM")));
+                    assert_eq!(field(report, "AoA Azimuth").unwrap().value.as_f64(), Some(-0.5));
+                    assert_eq!(field(report, "Frame Number").unwrap().value, Value::Dec(10));
+                    let rx_ts = field(report, "RX Timestamp").unwrap();
+                    assert_eq!(rx_ts.offset, Some(start + mac.len() / 2 + 14));
+                    assert_eq!(rx_ts.raw, vec![0xa1; rx]);
+                    assert_eq!(field(report, "UL-TDoA Device ID").map(|p| p.raw.clone()), (device > 0).then(|| vec![0xd1; device]));
+                    assert_eq!(field(report, "TX Timestamp").map(|p| p.raw.clone()), (tx > 0).then(|| vec![0xc1; tx]));
+                }
+            }
+        }
+
+        /* a truncated optional field */
+        let report = format!("341200100100c0ff000000050a000000{}{}", "a1".repeat(5), "c1".repeat(7));
+        assert!(decode_packet(&range_data_ntf(0, 0, &[report])).is_err());
+    }
+
+    #[test]
+    fn range_data_dl_tdoa() {
+        /* TX/RX timestamp sizes, anchor location (none, WGS-84, relative) and active ranging rounds */
+        for (control, tx, rx, location, rounds) in [
+            (0x0000u16, 5, 5, 0, 0), (0x0002, 8, 5, 0, 0), (0x0008, 5, 8, 0, 0), (0x000a, 8, 8, 0, 0),
+            (0x0020, 5, 5, 12, 0), (0x0040, 5, 5, 10, 0), (0x0060, 5, 5, 0, 0),
+            (0x0180, 5, 5, 0, 3), (0x0780, 5, 5, 0, 15), (0x01aa, 8, 8, 12, 3), (0x00ca, 8, 8, 10, 1),
+        ] {
+            let report = format!("34120002{:04x}010002000000000000003c{}{}000800f80a000000140000000500{}{}",
+                                 control.swap_bytes(), "e1".repeat(tx), "a1".repeat(rx),
+                                 "b1".repeat(location), &"0102030405060708090a0b0c0d0e0f"[..rounds * 2]);
+            let pkt = range_data_ntf(2, 0, &[report.clone(), report.clone()]);
+            let reports = reports_to_end(&pkt);
+            let size = report.len() / 2;
+            assert_eq!(size, 31 + tx + rx + location + rounds);
+
+            for (i, report) in reports.iter().enumerate() {
+                let start = 25 + i * size;
+                assert_eq!(report[0].offset, Some(start), "control {:#x}", control);
+                assert_eq!(field(report, "Message Type").unwrap().value, Value::Enum(2, Some("Response DTM")));
+                assert_eq!(field(report, "Message Control").unwrap().value, Value::Hex(control.into()));
+                assert_eq!(field(report, "Block Index").unwrap().value, Value::Dec(1));
+                assert_eq!(field(report, "Round Index").unwrap().value, Value::Dec(2));
+                assert_eq!(field(report, "RSSI").unwrap().value.as_f64(), Some(30.0));
+                assert_eq!(field(report, "TX Timestamp").unwrap().raw, vec![0xe1; tx]);
+                let rx_ts = field(report, "RX Timestamp").unwrap();
+                assert_eq!(rx_ts.offset, Some(start + 17 + tx));
+                assert_eq!(rx_ts.raw, vec![0xa1; rx]);
+                assert_eq!(field(report, "Anchor CFO").unwrap().value.as_f64(), Some(1.0));
+                assert_eq!(field(report, "CFO").unwrap().value.as_f64(), Some(-1.0));
+                assert_eq!(field(report, "Initiator Reply Time").unwrap().value, Value::Dec(10));
+                assert_eq!(field(report, "Responder Reply Time").unwrap().value, Value::Dec(20));
+                assert_eq!(field(report, "Initiator-Responder ToF").unwrap().value, Value::Dec(5));
+                assert_eq!(field(report, "DT-Anchor Location").map(|p| p.raw.clone()), (location > 0).then(|| vec![0xb1; location]));
+                assert_eq!(field(report, "Active Ranging Rounds").map(|p| p.raw.clone()),
+                           (rounds > 0).then(|| (1..=rounds as u8).collect::<Vec<_>>()));
+            }
+        }
+    }
+
+    /* OWR for AoA report: NLoS, frame sequence number 7, block 2, azimuth 1°, elevation -1° */
+    fn owr_aoa_report(mac: &str) -> String {
+        format!("{}000107020080006480ff32", mac)
+    }
+
+    fn assert_owr_aoa(report: &[Param]) {
+        assert_eq!(field(report, "NLoS").unwrap().value, Value::Enum(1, Some("NLoS")));
+        assert_eq!(field(report, "Frame Sequence Number").unwrap().value, Value::Dec(7));
+        assert_eq!(field(report, "Block Index").unwrap().value, Value::Dec(2));
+        assert_eq!(field(report, "AoA Azimuth").unwrap().value.as_f64(), Some(1.0));
+        assert_eq!(field(report, "AoA Azimuth FOM").unwrap().value, Value::Dec(100));
+        assert_eq!(field(report, "AoA Elevation").unwrap().value.as_f64(), Some(-1.0));
+        assert_eq!(field(report, "AoA Elevation FOM").unwrap().value, Value::Dec(50));
+    }
+
+    #[test]
+    fn range_data_owr_aoa() {
+        for (mac_mode, mac) in [(0u8, "3412"), (1, "0807060504030201")] {
+            let pkt = range_data_ntf(3, mac_mode, &[owr_aoa_report(mac), owr_aoa_report(mac)]);
+            let msg = message_in(&pkt, &Context::default());
+            assert_eq!(msg.get("Ranging type"), Some(&Value::Enum(3, Some("OWR AoA"))));
+            assert!(msg.comments.is_empty());
+            let reports = reports_to_end(&pkt);
+            assert_eq!(reports.len(), 2);
+            assert_eq!(reports[1][0].offset, Some(25 + 11 + mac.len() / 2));
+            reports.iter().for_each(|r| assert_owr_aoa(r));
+
+            let UciMessage::RangeDataNtf(ntf) = decode_packet(&pkt).unwrap() else { panic!() };
+            assert_eq!(ntf.measurements.len(), 2);
+            for m in &ntf.measurements {
+                let Measurement::OwrAoa(owr) = m else { panic!("{:?}", m) };
+                assert_eq!((owr.frame_sequence_number, owr.block_index), (7, 2));
+                assert_eq!(owr.aoa_azimuth.angle, 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn range_data_owr_aoa_as_dl_tdoa() {
+        /* type 0x02 whose reports are exactly OWR for AoA sized */
+        let pkt = range_data_ntf(2, 0, &[owr_aoa_report("3412"), owr_aoa_report("3412")]);
+        let msg = message_in(&pkt, &Context::default());
+        assert_eq!(msg.get("Ranging type"), Some(&Value::Enum(2, Some("Down TDoA"))));
+        assert_eq!(msg.comments, vec!["Reports sized as OWR AoA measurements"]);
+        let reports = reports_to_end(&pkt);
+        reports.iter().for_each(|r| assert_owr_aoa(r));
+
+        /* a real DL-TDoA report of the same count stays DL-TDoA */
+        let dl_tdoa = format!("34120001{}", "00".repeat(37));
+        let pkt = range_data_ntf(2, 0, &[dl_tdoa.clone(), dl_tdoa]);
+        let msg = message_in(&pkt, &Context::default());
+        assert!(msg.comments.is_empty());
+        let reports = reports_to_end(&pkt);
+        assert_eq!(reports.len(), 2);
+        assert!(field(&reports[0], "Message Type").is_some());
+        assert!(field(&reports[0], "Frame Sequence Number").is_none());
+        assert_eq!(reports[1][0].offset, Some(25 + 41));
+
+        /* neither size: decoded, and rejected, as DL-TDoA */
+        let pkt = range_data_ntf(2, 0, &[owr_aoa_report("3412") + "00"]);
+        assert!(decode_packet(&pkt).is_err());
+    }
+
+    #[test]
+    fn ranging_count() {
+        let msg = decode_hex("42030005000a010000");
+        assert_eq!(msg.get("STATUS"), Some(&Value::Enum(0, Some("OK"))));
+        assert_eq!(msg.get("COUNT"), Some(&Value::Dec(266)));
+        assert_eq!(msg.params.len(), 2);
+
+        /* a failed request carries the status alone */
+        let msg = decode_hex("4203000111");
+        assert_eq!(msg.params.len(), 1);
+        assert_eq!(msg.get("STATUS"), Some(&Value::Enum(0x11, Some("ERROR_SESSION_NOT_EXIST"))));
+
+        /* but a successful one needs its count */
+        assert!(decode_packet(&Packet::from_hex("420300030000aa").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_per_rx_ntf() {
+        let counters: String = (1..=13u32).map(|n| format!("{:08x}", n.swap_bytes())).collect();
+        let msg = decode_hex(&format!("6d03003700{}aabb", counters));
+        assert_eq!(msg.get("ATTEMPTS"), Some(&Value::Dec(1)));
+        assert_eq!(msg.get("PSDU_BIT_ERROR"), Some(&Value::Dec(11)));
+        assert_eq!(msg.get("EOF"), Some(&Value::Dec(13)));
+        assert_eq!(msg.get("VENDOR_SPECIFIC_DATA"), Some(&Value::Bytes(vec![0xaa, 0xbb])));
+    }
+
+    #[test]
+    fn test_loopback_ntf() {
+        let header = "00010000000200030000000400c0f98000a20c";
+        let msg = decode_hex(&format!("6d060018{}0300112233", header));
+        assert_eq!(msg.get("RX_TS_INT"), Some(&Value::Dec(3)));
+        assert_eq!(msg.get("AOA_AZIMUTH").and_then(Value::as_f64), Some(-12.5));
+        assert_eq!(msg.get("PHR"), Some(&Value::Hex(0xca2)));
+        let len = msg.param("PSDU_DATA_LENGTH").unwrap();
+        assert_eq!((len.offset, &len.value), (Some(19), &Value::Dec(3)));
+        let data = msg.param("PSDU_DATA").unwrap();
+        assert_eq!((data.offset, &data.value), (Some(21), &Value::Bytes(vec![0x11, 0x22, 0x33])));
+        assert!(msg.get("VENDOR_SPECIFIC_DATA").is_none());
+
+        /* the PSDU is followed by vendor data, or cut short */
+        let msg = decode_hex(&format!("6d060018{}0200112233", header));
+        assert_eq!(msg.get("PSDU_DATA"), Some(&Value::Bytes(vec![0x11, 0x22])));
+        assert_eq!(msg.get("VENDOR_SPECIFIC_DATA"), Some(&Value::Bytes(vec![0x33])));
+        let err = decode_packet(&Packet::from_hex(&format!("6d060017{}04001122", header)).unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "psdu length mismatch: expected=4 actual=2");
+    }
+
+    #[test]
+    fn se_binding() {
+        let msg = decode_hex("4e0a000400020305");
+        assert_eq!(msg.get("STATUS"), Some(&Value::Enum(0, Some("OK"))));
+        assert_eq!(msg.get("Binding state"), Some(&Value::Enum(2, Some("Bound,locked"))));
+        assert_eq!(msg.get("SE binding count"), Some(&Value::Dec(3)));
+        assert_eq!(msg.get("UWBS binding count"), Some(&Value::Dec(5)));
+
+        /* a failed request carries the status alone */
+        let msg = decode_hex("4e0a000101");
+        assert_eq!(msg.params.len(), 1);
+        assert_eq!(msg.get("STATUS"), Some(&Value::Enum(1, Some("REJECTED"))));
+
+        let msg = decode_hex("6e0100020004");
+        assert_eq!(msg.name, "NXP_SE_DO_BIND_NTF");
+        assert_eq!(msg.get("STATUS"), Some(&Value::Enum(0, Some("OK"))));
+        assert_eq!(msg.get("Remaining binding count"), Some(&Value::Dec(4)));
+        assert!(decode_packet(&Packet::from_hex("6e01000100").unwrap()).is_err());
+    }
+}